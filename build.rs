@@ -1,8 +1,27 @@
 fn main() {
-    cc::Build::new()
-        .cpp(true)
-        .flag("-std=c++11")
-        .include("gmod-module-base/include/GarrysMod/Lua")
-        .file("src/bridge.cpp")
-        .compile("bridge");
+    #[cfg(feature = "test-backend")]
+    {
+        // implements the bridge over a vendored lua 5.1 instead of the game's ILuaBase
+        let lua = lua_src::Build::new().build(lua_src::Lua51);
+        lua.print_cargo_metadata();
+        cc::Build::new()
+            .include(lua.include_dir())
+            .file("src/test_backend.c")
+            .compile("bridge");
+        println!("cargo:rerun-if-changed=src/test_backend.c");
+    }
+    #[cfg(not(feature = "test-backend"))]
+    {
+        cc::Build::new()
+            .cpp(true)
+            .flag("-std=c++11")
+            .include("gmod-module-base/include/GarrysMod/Lua")
+            .file("src/bridge.cpp")
+            .compile("bridge");
+        println!("cargo:rerun-if-changed=src/bridge.cpp");
+        // lua_shared functions are looked up with dlsym
+        if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux") {
+            println!("cargo:rustc-link-lib=dl");
+        }
+    }
 }
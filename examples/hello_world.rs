@@ -24,11 +24,11 @@ fn sub(state: LuaState) -> lua::Result<f64> {
 
 #[gmrs::entry]
 fn main(state: LuaState) {
-    gmrs::set_global(state, "rust_hello_world", NativeFunc::new(hello_world));
+    gmrs::set_global(state, "rust_hello_world", NativeFunc::new(hello_world)).unwrap();
     let tbl = lua::create_table(state);
     tbl.set(state, "add", NativeFunc::new(add));
     tbl.set(state, "sub", NativeFunc::new(sub));
-    gmrs::set_global(state, "rust_arithmetic", tbl);
+    gmrs::set_global(state, "rust_arithmetic", tbl).unwrap();
 }
 
 #[gmrs::exit]
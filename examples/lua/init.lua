@@ -0,0 +1,8 @@
+-- runs after the native functions are registered, the module table is passed as `...`
+local lua_scripts = ...
+
+function lua_scripts.greet_all()
+    for _, ply in ipairs(player.GetAll()) do
+        ply:ChatPrint(lua_scripts.greeting(ply:Nick()))
+    end
+end
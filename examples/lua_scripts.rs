@@ -0,0 +1,16 @@
+use gmrs::prelude::*;
+
+#[gmrs::function]
+fn greeting(name: String) -> lua::Result<String> {
+    Ok(format!("Hello {} from rust", name))
+}
+
+#[gmrs::entry(module = "lua_scripts", scripts = [gmrs::include_lua!("lua/init.lua")])]
+fn main(state: LuaState) {
+    let tbl = lua::create_table(state);
+    tbl.set(state, "greeting", NativeFunc::new(greeting));
+    gmrs::set_global(state, "lua_scripts", tbl).unwrap();
+}
+
+#[gmrs::exit]
+fn exit(_state: LuaState) {}
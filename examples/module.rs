@@ -0,0 +1,48 @@
+// `rust_arithmetic.add(1, 2)`, `rust_arithmetic.Counter(10):increment()`, `rust_arithmetic._VERSION`
+#[gmrs::module(name = "rust_arithmetic", version)]
+mod arithmetic {
+    use gmrs::prelude::*;
+
+    #[constant]
+    const PI: f64 = std::f64::consts::PI;
+
+    #[function]
+    fn add(a: f64, b: f64) -> lua::Result<f64> {
+        Ok(a + b)
+    }
+
+    #[function(name = "sub")]
+    fn subtract(a: f64, b: f64) -> lua::Result<f64> {
+        Ok(a - b)
+    }
+
+    pub struct Counter(f64);
+    impl UserType for Counter {
+        fn build_metatable(builder: &mut MetatableBuilder<Self>) {
+            builder.method("increment", counter_increment);
+        }
+    }
+
+    #[gmrs::function(name = "increment")]
+    fn counter_increment(this: UserData<Counter>) -> lua::Result<f64> {
+        Ok(this.with(|counter| {
+            counter.0 += 1.0;
+            counter.0
+        }))
+    }
+
+    // registered as `Counter`, the name of the user data type
+    #[constructor]
+    fn new_counter(start: f64) -> lua::Result<UserData<Counter>> {
+        Ok(UserData::new(Counter(start)))
+    }
+
+    // runs after the table is registered
+    #[entry]
+    fn init(state: LuaState) {
+        gmrs::print!(state, "rust_arithmetic loaded");
+    }
+}
+
+#[gmrs::exit]
+fn exit(_state: gmrs::lua::LuaState) {}
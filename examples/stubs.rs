@@ -0,0 +1,31 @@
+// `cargo run --example stubs -- <directory>` writes the lua stubs and markdown references of the
+// module and of the global functions, `stubs` by default
+#[gmrs::module(name = "rust_strings")]
+mod strings {
+    use gmrs::prelude::*;
+
+    /// Repeats `text` `times` times.
+    #[function]
+    fn repeat(text: &str, times: usize) -> lua::Result<String> {
+        Ok(text.repeat(times))
+    }
+
+    /// Returns the arguments unchanged.
+    #[function]
+    fn identity(values: lua::MultiValue) -> lua::Result<lua::MultiValue> {
+        Ok(values)
+    }
+}
+
+/// Joins the parts with `separator`.
+#[gmrs::function]
+fn join(separator: &str, parts: gmrs::lua::Sequence<String>) -> gmrs::lua::Result<String> {
+    Ok(parts.0.join(separator))
+}
+
+fn main() -> std::io::Result<()> {
+    gmrs::stubs::Stubs::new("rust_globals")
+        .module(strings::MODULE_INFO)
+        .function(gmrs::function_info!(join))
+        .generate()
+}
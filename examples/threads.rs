@@ -31,7 +31,8 @@ fn main(state: LuaState) {
         state,
         "some_long_computation",
         NativeFunc::new(some_long_computation),
-    );
+    )
+    .unwrap();
 }
 
 #[gmrs::exit]
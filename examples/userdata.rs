@@ -113,13 +113,13 @@ impl UserType for GmodTcp {
     }
 }
 
-#[gmrs::function]
+#[gmrs::function(name = "send")]
 fn gmod_tcp_send(this: UserData<GmodTcp>, data: Vec<u8>) -> lua::Result<()> {
     this.with(|tcp| tcp.sender.send(data))?;
     Ok(())
 }
 
-#[gmrs::function]
+#[gmrs::function(name = "on_recv")]
 // on_receive : function(socket, data, error)
 fn gmod_tcp_on_receive(
     this: UserData<GmodTcp>,
@@ -163,7 +163,7 @@ fn socket_connect(addr: String, on_connect: OwnedRef) -> lua::Result<()> {
 fn main(state: LuaState) {
     let socket = lua::create_table(state);
     socket.set(state, "connect", NativeFunc::new(socket_connect));
-    gmrs::set_global(state, "socket", socket);
+    gmrs::set_global(state, "socket", socket).unwrap();
 }
 
 #[gmrs::exit]
@@ -1,7 +1,45 @@
 use proc_macro2::TokenStream;
-use syn::{ItemFn, Result};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Expr, ItemFn, LitStr, Result, Token,
+};
 
-fn validate_entry(item: ItemFn) -> Result<TokenStream> {
+/// `#[gmrs::entry(module = "...", scripts = [...])]`
+#[derive(Default)]
+struct EntryArgs {
+    module: Option<LitStr>,
+    scripts: Vec<Expr>,
+}
+
+impl Parse for EntryArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = Self::default();
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if key == "module" {
+                args.module = Some(input.parse()?);
+            } else if key == "scripts" {
+                let content;
+                syn::bracketed!(content in input);
+                let scripts = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+                args.scripts.extend(scripts);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "Expected `module = \"...\"` or `scripts = [...]`",
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+fn validate_entry(args: EntryArgs, item: ItemFn) -> Result<TokenStream> {
     if item.sig.inputs.len() != 1 {
         return Err(syn::Error::new_spanned(
             &item,
@@ -9,6 +47,20 @@ fn validate_entry(item: ItemFn) -> Result<TokenStream> {
         ));
     }
     let name = &item.sig.ident;
+    let run_scripts = if args.scripts.is_empty() {
+        quote::quote! {}
+    } else {
+        let module = match &args.module {
+            Some(module) => quote::quote! { Some(#module) },
+            None => quote::quote! { None },
+        };
+        let scripts = &args.scripts;
+        quote::quote! {
+            if let Err(e) = gmrs::scripts::run_all(state, #module, &[#(#scripts),*]) {
+                gmrs::error_no_halt(state, &format!("{}\n", e));
+            }
+        }
+    };
     Ok(quote::quote! {
         #[no_mangle]
         pub extern "C" fn gmod13_open(raw: gmrs::lua::LuaStateRaw) -> u32 {
@@ -20,6 +72,7 @@ fn validate_entry(item: ItemFn) -> Result<TokenStream> {
             };
             #item
             let _ = #name(state);
+            #run_scripts
             gmrs::internal::unset_lua_state_raw();
             0
         }
@@ -27,11 +80,12 @@ fn validate_entry(item: ItemFn) -> Result<TokenStream> {
 }
 
 pub fn parse(
-    _args: proc_macro::TokenStream,
+    args: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let args = syn::parse_macro_input!(args as EntryArgs);
     let item = syn::parse_macro_input!(input as ItemFn);
-    match validate_entry(item) {
+    match validate_entry(args, item) {
         Ok(t) => t.into(),
         Err(e) => e.into_compile_error().into(),
     }
@@ -20,6 +20,11 @@ fn validate_exit(item: ItemFn) -> Result<TokenStream> {
             };
             #item
             let _ = #name(state);
+            gmrs::hooks::remove_all(state);
+            gmrs::concommand::remove_all(state);
+            gmrs::convar::remove_all(state);
+            gmrs::net::remove_all(state);
+            gmrs::globals::clear_cache(state);
             gmrs::internal::unset_lua_state_raw();
             0
         }
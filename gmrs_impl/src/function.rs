@@ -1,4 +1,11 @@
-use syn::{FnArg, ItemFn, Result};
+use syn::{AttributeArgs, FnArg, ItemFn, Lit, Meta, NestedMeta, Pat, Result, Type};
+
+/// `true` for `&'static T`, other `'static` borrows are rejected by the `FromStackRef` bound
+/// with a less helpful error.
+fn is_static_reference(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(reference)
+        if reference.lifetime.as_ref().is_some_and(|lifetime| lifetime.ident == "static"))
+}
 
 fn parse_args_list(sig: &syn::Signature) -> Result<Vec<syn::PatType>> {
     let mut args = Vec::new();
@@ -10,54 +17,112 @@ fn parse_args_list(sig: &syn::Signature) -> Result<Vec<syn::PatType>> {
                     "Receiver arguments are now allowed here",
                 ))
             }
-            FnArg::Typed(pat) => args.push(pat.clone()),
+            FnArg::Typed(pat) => {
+                let ty = &pat.ty;
+                if is_static_reference(ty) {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        "Borrowed arguments only live until the function returns, they cant be 'static",
+                    ));
+                }
+                args.push(pat.clone())
+            }
         }
     }
     Ok(args)
 }
 
+/// Parses `#[gmrs::function(name = "...")]`, returns the name to be used in error messages.
+fn parse_lua_name(args: AttributeArgs, item: &ItemFn) -> Result<String> {
+    let mut name = item.sig.ident.to_string();
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => match nv.lit {
+                Lit::Str(s) => name = s.value(),
+                other => return Err(syn::Error::new_spanned(other, "Expected a string literal")),
+            },
+            other => return Err(syn::Error::new_spanned(other, "Unknown argument")),
+        }
+    }
+    Ok(name)
+}
+
 pub fn parse(
-    _args: proc_macro::TokenStream,
+    args: proc_macro::TokenStream,
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let attr_args = syn::parse_macro_input!(args as AttributeArgs);
     let item = syn::parse_macro_input!(input as ItemFn);
 
     let vis = &item.vis;
     let name = &item.sig.ident;
 
+    let lua_name = match parse_lua_name(attr_args, &item) {
+        Ok(lua_name) => lua_name,
+        Err(e) => return e.into_compile_error().into(),
+    };
     let args = match parse_args_list(&item.sig) {
         Ok(args) => args,
         Err(e) => return e.into_compile_error().into(),
     };
     let arg_ty = args.iter().map(|arg| &arg.ty);
-    // let arg_num: Vec<_> = (0..arg_ty.len()).collect();
     let arg_name: Vec<_> = args.iter().map(|arg| &arg.pat).collect();
+    let arg_name_str: Vec<_> = arg_name
+        .iter()
+        .map(|pat| match pat.as_ref() {
+            Pat::Ident(ident) => ident.ident.to_string(),
+            other => quote::quote!(#other).to_string(),
+        })
+        .collect();
+    let info_name = quote::format_ident!("__gmrs_info_{}", name);
+    let docs = crate::stubs::doc_comment(&item.attrs);
+    let (param_name, param_ty): (Vec<_>, Vec<_>) = args
+        .iter()
+        .zip(&arg_name_str)
+        .filter(|(arg, _)| !crate::stubs::is_lua_state(&arg.ty))
+        .map(|(arg, name)| (name, crate::stubs::lua_type(&arg.ty)))
+        .unzip();
+    let returns = crate::stubs::return_types(&item.sig.output);
 
     (quote::quote! {
         #vis unsafe extern "C" fn #name(raw : gmrs::lua::LuaStateRaw) -> i32 {
             unsafe { gmrs::internal::set_lua_state_raw(raw) };
             #item
                 let state = unsafe { gmrs::lua::LuaState::new(raw) };
-                unsafe fn __inner_native_func_wrapper(state : gmrs::lua::LuaState) -> gmrs::lua::Result<i32> {
+                // borrowed arguments only live for `'args`, a lifetime chosen by the caller, so
+                // they cant be declared as `'static` and escape the call
+                unsafe fn __inner_native_func_wrapper<'args>(state : gmrs::lua::LuaState) -> gmrs::lua::Result<i32> {
                     let mut stack_offset = 1;
                     #(
-                        let (#arg_name, push_count) : (#arg_ty, i32) = <#arg_ty as gmrs::lua::FromStack>::from_stack(state, stack_offset)?;
+                        let (#arg_name, push_count) : (#arg_ty, i32) = <#arg_ty as gmrs::lua::FromStackRef<'args>>::from_stack_ref(state, stack_offset)
+                            .map_err(|e| gmrs::lua::Error::bad_argument(stack_offset, #lua_name, e))?;
                         stack_offset += push_count;
                     )*
                     let result = #name(#(#arg_name),*)?;
-                    Ok(gmrs::lua::push(state, result))
+                    gmrs::lua::try_push(state, result)
                 }
+                #[cfg(debug_assertions)]
+                let initial_top = gmrs::lua::top(state);
                 let result = unsafe { __inner_native_func_wrapper(state) };
+                #[cfg(debug_assertions)]
+                if let Ok(count) = result {
+                    gmrs::internal::check_stack_balance(state, #lua_name, initial_top + count);
+                }
                 gmrs::internal::unset_lua_state_raw();
                 match result {
                     Ok(count) => count,
-                    Err(e) => {
-                        let msg = format!("{}", e);
-                        drop(e);
-                        unsafe { gmrs::lua::throw_error(state, msg) };
-                    }
+                    Err(e) => unsafe { gmrs::lua::throw_error(state, e) },
                 }
         }
+
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        #vis const #info_name: gmrs::stubs::FunctionInfo = gmrs::stubs::FunctionInfo {
+            name: #lua_name,
+            docs: #docs,
+            params: &[#(gmrs::stubs::ParamInfo { name: #param_name, ty: #param_ty }),*],
+            returns: &[#(#returns),*],
+        };
     })
     .into()
 }
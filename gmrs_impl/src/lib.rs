@@ -1,7 +1,10 @@
 mod entry;
 mod exit;
 mod function;
+mod module;
+mod net_message;
 mod raw_function;
+mod stubs;
 
 #[proc_macro_attribute]
 pub fn entry(
@@ -27,6 +30,14 @@ pub fn function(
     function::parse(args, input)
 }
 
+#[proc_macro_attribute]
+pub fn module(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    module::parse(args, input)
+}
+
 #[proc_macro_attribute]
 pub fn raw_function(
     args: proc_macro::TokenStream,
@@ -34,3 +45,14 @@ pub fn raw_function(
 ) -> proc_macro::TokenStream {
     raw_function::parse(args, input)
 }
+
+/// The `gmrs::stubs::FunctionInfo` of a function declared with `#[gmrs::function]`.
+#[proc_macro]
+pub fn function_info(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    stubs::function_info(input)
+}
+
+#[proc_macro_derive(NetMessage, attributes(net))]
+pub fn net_message(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    net_message::parse(input)
+}
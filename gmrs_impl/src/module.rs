@@ -0,0 +1,283 @@
+use proc_macro2::TokenStream;
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Attribute, Expr, GenericArgument, Item, ItemMod, Lit, LitStr, Meta, NestedMeta, PathArguments,
+    Result, ReturnType, Token, Type,
+};
+
+/// `#[gmrs::module(name = "...", version = "...", scripts = [...])]`
+#[derive(Default)]
+struct ModuleArgs {
+    name: Option<LitStr>,
+    version: Option<TokenStream>,
+    scripts: Vec<Expr>,
+}
+
+impl Parse for ModuleArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = Self::default();
+        while !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key == "version" && (input.is_empty() || input.peek(Token![,])) {
+                // `version` alone uses the version of the crate
+                args.version = Some(quote::quote! { env!("CARGO_PKG_VERSION") });
+            } else {
+                input.parse::<Token![=]>()?;
+                if key == "name" {
+                    args.name = Some(input.parse()?);
+                } else if key == "version" {
+                    let version: LitStr = input.parse()?;
+                    args.version = Some(quote::quote! { #version });
+                } else if key == "scripts" {
+                    let content;
+                    syn::bracketed!(content in input);
+                    let scripts = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+                    args.scripts.extend(scripts);
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "Expected `name = \"...\"`, `version`, `version = \"...\"` or `scripts = [...]`",
+                    ));
+                }
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// The attributes recognized on the items of the module.
+enum ItemKind {
+    Function,
+    Constructor,
+    Constant,
+    Entry,
+}
+
+/// Removes the first attribute of `attrs` recognized by the module and returns it.
+fn take_attribute(attrs: &mut Vec<Attribute>) -> Option<(ItemKind, Attribute)> {
+    let position = attrs.iter().position(|attr| attribute_kind(attr).is_some())?;
+    let attr = attrs.remove(position);
+    Some((attribute_kind(&attr)?, attr))
+}
+
+/// Matches the bare `#[name]` markers, `#[gmrs::function]` is left alone so native functions that
+/// arent exported, like methods, can be declared in the module.
+fn attribute_kind(attr: &Attribute) -> Option<ItemKind> {
+    let name = attr.path.get_ident()?.to_string();
+    match name.as_str() {
+        "function" => Some(ItemKind::Function),
+        "constructor" => Some(ItemKind::Constructor),
+        "constant" => Some(ItemKind::Constant),
+        "entry" => Some(ItemKind::Entry),
+        _ => None,
+    }
+}
+
+/// Returns the `name = "..."` argument of the attribute.
+fn attribute_name(attr: &Attribute) -> Result<Option<String>> {
+    match attr.parse_meta()? {
+        Meta::Path(_) => Ok(None),
+        Meta::List(list) => {
+            let mut name = None;
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                        match nv.lit {
+                            Lit::Str(s) => name = Some(s.value()),
+                            other => {
+                                return Err(syn::Error::new_spanned(other, "Expected a string"))
+                            }
+                        }
+                    }
+                    other => return Err(syn::Error::new_spanned(other, "Expected `name = \"...\"`")),
+                }
+            }
+            Ok(name)
+        }
+        Meta::NameValue(nv) => Err(syn::Error::new_spanned(nv, "Expected `name = \"...\"`")),
+    }
+}
+
+/// Finds `T` in a return type like `lua::Result<UserData<T>>`.
+fn user_data_type(output: &ReturnType) -> Option<String> {
+    fn find(ty: &Type) -> Option<String> {
+        let path = match ty {
+            Type::Path(path) => &path.path,
+            _ => return None,
+        };
+        let segment = path.segments.last()?;
+        let args = match &segment.arguments {
+            PathArguments::AngleBracketed(args) => &args.args,
+            _ => return None,
+        };
+        let mut inner = args.iter().filter_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        });
+        if segment.ident == "UserData" {
+            match inner.next()? {
+                Type::Path(path) => Some(path.path.segments.last()?.ident.to_string()),
+                _ => None,
+            }
+        } else {
+            inner.find_map(find)
+        }
+    }
+    match output {
+        ReturnType::Type(_, ty) => find(ty),
+        ReturnType::Default => None,
+    }
+}
+
+fn expand_module(args: ModuleArgs, mut module: ItemMod) -> Result<TokenStream> {
+    let (_, items) = match &mut module.content {
+        Some(content) => content,
+        None => {
+            return Err(syn::Error::new_spanned(
+                &module,
+                "The module must be declared inline, `mod name { ... }`",
+            ))
+        }
+    };
+    let mod_name = &module.ident;
+    let lua_name = match &args.name {
+        Some(name) => name.value(),
+        None => mod_name.to_string(),
+    };
+
+    let mut registrations = Vec::new();
+    let mut function_infos = Vec::new();
+    let mut constant_infos = Vec::new();
+    let mut entry = None;
+    for item in items.iter_mut() {
+        match item {
+            Item::Fn(func) => {
+                let (kind, attr) = match take_attribute(&mut func.attrs) {
+                    Some(found) => found,
+                    None => continue,
+                };
+                let ident = func.sig.ident.clone();
+                let key = match kind {
+                    ItemKind::Entry => {
+                        if entry.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                attr,
+                                "The module can only have one entry function",
+                            ));
+                        }
+                        entry = Some(ident);
+                        continue;
+                    }
+                    ItemKind::Function => {
+                        attribute_name(&attr)?.unwrap_or_else(|| ident.to_string())
+                    }
+                    ItemKind::Constructor => match attribute_name(&attr)? {
+                        Some(name) => name,
+                        None => user_data_type(&func.sig.output).ok_or_else(|| {
+                            syn::Error::new_spanned(
+                                &func.sig,
+                                "Constructors must return a `UserData<T>`, set the name with `#[constructor(name = \"...\")]`",
+                            )
+                        })?,
+                    },
+                    ItemKind::Constant => {
+                        return Err(syn::Error::new_spanned(
+                            attr,
+                            "`#[constant]` can only be used on constants",
+                        ))
+                    }
+                };
+                func.attrs
+                    .push(syn::parse_quote! { #[gmrs::function(name = #key)] });
+                registrations.push(quote::quote! {
+                    module.set(state, #key, gmrs::lua::NativeFunc::new(#ident));
+                });
+                function_infos.push(quote::format_ident!("__gmrs_info_{}", ident));
+            }
+            Item::Const(constant) => {
+                let (kind, attr) = match take_attribute(&mut constant.attrs) {
+                    Some(found) => found,
+                    None => continue,
+                };
+                if !matches!(kind, ItemKind::Constant) {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "Only `#[constant]` can be used on constants",
+                    ));
+                }
+                let ident = &constant.ident;
+                let key = attribute_name(&attr)?.unwrap_or_else(|| ident.to_string());
+                registrations.push(quote::quote! {
+                    module.set(state, #key, #ident);
+                });
+                let ty = crate::stubs::lua_type(&constant.ty);
+                let docs = crate::stubs::doc_comment(&constant.attrs);
+                constant_infos.push(quote::quote! {
+                    gmrs::stubs::ConstantInfo { name: #key, ty: #ty, docs: #docs }
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(version) = &args.version {
+        registrations.push(quote::quote! {
+            module.set(state, "_VERSION", #version);
+        });
+    }
+    let docs = crate::stubs::doc_comment(&module.attrs);
+    let version = match &args.version {
+        Some(version) => quote::quote! { Some(#version) },
+        None => quote::quote! { None },
+    };
+    items.push(syn::parse_quote! {
+        /// Describes the registered items, used by `gmrs::stubs` to generate the lua stubs.
+        pub const MODULE_INFO: gmrs::stubs::ModuleInfo = gmrs::stubs::ModuleInfo {
+            name: #lua_name,
+            version: #version,
+            docs: #docs,
+            functions: &[#(#function_infos),*],
+            constants: &[#(#constant_infos),*],
+        };
+    });
+    let call_entry = entry.map(|entry| quote::quote! { let _ = #entry(state); });
+    items.push(syn::parse_quote! {
+        pub(super) fn __gmrs_register_module(state: gmrs::lua::LuaState) {
+            {
+                let _guard = gmrs::lua::StackGuard::new(state);
+                let module = gmrs::lua::create_table(state);
+                #(#registrations)*
+                if let Err(e) = gmrs::set_global(state, #lua_name, module) {
+                    gmrs::error_no_halt(state, &format!("{}\n", e));
+                }
+            }
+            #call_entry
+        }
+    });
+
+    let scripts = &args.scripts;
+    Ok(quote::quote! {
+        #module
+
+        #[gmrs::entry(module = #lua_name, scripts = [#(#scripts),*])]
+        fn __gmrs_module_entry(state: gmrs::lua::LuaState) {
+            #mod_name::__gmrs_register_module(state);
+        }
+    })
+}
+
+pub fn parse(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = syn::parse_macro_input!(args as ModuleArgs);
+    let module = syn::parse_macro_input!(input as ItemMod);
+    match expand_module(args, module) {
+        Ok(t) => t.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
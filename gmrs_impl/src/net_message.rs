@@ -0,0 +1,136 @@
+use proc_macro2::TokenStream;
+use syn::{Attribute, Data, DeriveInput, Lit, Meta, NestedMeta, Result};
+
+enum FieldKind {
+    Value,
+    Bits(u32),
+    Skip,
+}
+
+/// Parses the `#[net(...)]` attributes, `func` is called for every argument.
+fn parse_net_attrs<F>(attrs: &[Attribute], mut func: F) -> Result<()>
+where
+    F: FnMut(NestedMeta) -> Result<()>,
+{
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("net")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => {
+                for nested in list.nested {
+                    func(nested)?;
+                }
+            }
+            other => return Err(syn::Error::new_spanned(other, "Expected #[net(...)]")),
+        }
+    }
+    Ok(())
+}
+
+fn parse_message_name(input: &DeriveInput) -> Result<String> {
+    let mut name = input.ident.to_string();
+    parse_net_attrs(&input.attrs, |arg| match arg {
+        NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => match nv.lit {
+            Lit::Str(s) => {
+                name = s.value();
+                Ok(())
+            }
+            other => Err(syn::Error::new_spanned(other, "Expected a string literal")),
+        },
+        other => Err(syn::Error::new_spanned(other, "Unknown argument")),
+    })?;
+    Ok(name)
+}
+
+fn parse_field_kind(field: &syn::Field) -> Result<FieldKind> {
+    let mut kind = FieldKind::Value;
+    parse_net_attrs(&field.attrs, |arg| match arg {
+        NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("bits") => match nv.lit {
+            Lit::Int(bits) => {
+                let value = bits.base10_parse::<u32>()?;
+                if !(1..=32).contains(&value) {
+                    return Err(syn::Error::new_spanned(
+                        bits,
+                        "The number of bits must be between 1 and 32",
+                    ));
+                }
+                kind = FieldKind::Bits(value);
+                Ok(())
+            }
+            other => Err(syn::Error::new_spanned(other, "Expected an integer literal")),
+        },
+        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+            kind = FieldKind::Skip;
+            Ok(())
+        }
+        other => Err(syn::Error::new_spanned(other, "Unknown argument")),
+    })?;
+    Ok(kind)
+}
+
+fn derive_net_message(input: DeriveInput) -> Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "NetMessage can only be derived for structs",
+            ))
+        }
+    };
+    let message_name = parse_message_name(&input)?;
+
+    let mut writes = Vec::new();
+    let mut reads = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let ty = &field.ty;
+        let member = match &field.ident {
+            Some(ident) => quote::quote!(#ident),
+            None => {
+                let index = syn::Index::from(index);
+                quote::quote!(#index)
+            }
+        };
+        let (write, read) = match parse_field_kind(field)? {
+            FieldKind::Value => (
+                quote::quote!(gmrs::net::NetValue::net_write(&self.#member, writer)?;),
+                quote::quote!(<#ty as gmrs::net::NetValue>::net_read(reader)?),
+            ),
+            FieldKind::Bits(bits) => (
+                quote::quote!(gmrs::net::NetBits::net_write_bits(&self.#member, writer, #bits)?;),
+                quote::quote!(<#ty as gmrs::net::NetBits>::net_read_bits(reader, #bits)?),
+            ),
+            FieldKind::Skip => (
+                quote::quote!(),
+                quote::quote!(<#ty as std::default::Default>::default()),
+            ),
+        };
+        writes.push(write);
+        reads.push(quote::quote!(#member: #read));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote::quote! {
+        impl #impl_generics gmrs::net::NetMessage for #name #ty_generics #where_clause {
+            const NAME: &'static str = #message_name;
+
+            #[allow(unused_variables)]
+            fn write(&self, writer: &mut gmrs::net::NetWriter) -> gmrs::lua::Result<()> {
+                #(#writes)*
+                Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn read(reader: &mut gmrs::net::NetReader) -> gmrs::lua::Result<Self> {
+                Ok(Self { #(#reads),* })
+            }
+        }
+    })
+}
+
+pub fn parse(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match derive_net_message(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.into_compile_error().into(),
+    }
+}
@@ -18,11 +18,7 @@ pub fn parse(
             gmrs::internal::unset_lua_state_raw();
             match result {
                 Ok(count) => count,
-                Err(e) => {
-                    let msg = format!("{}", e);
-                    drop(e);
-                    unsafe { gmrs::lua::throw_error(state, msg) };
-                }
+                Err(e) => unsafe { gmrs::lua::throw_error(state, e) },
             }
         }
     })
@@ -0,0 +1,141 @@
+//! Helpers used to record the metadata consumed by `gmrs::stubs`.
+use syn::{Attribute, GenericArgument, Lit, Meta, PathArguments, ReturnType, Type};
+
+/// Replaces the function name at the end of the path with the name of its info constant.
+pub fn function_info(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let mut path = syn::parse_macro_input!(input as syn::Path);
+    if let Some(segment) = path.segments.last_mut() {
+        segment.ident = quote::format_ident!("__gmrs_info_{}", segment.ident);
+    }
+    quote::quote!(#path).into()
+}
+
+/// Joins the `///` comments of an item, removing the space after `///`.
+pub fn doc_comment(attrs: &[Attribute]) -> String {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("doc")) {
+        if let Ok(Meta::NameValue(nv)) = attr.parse_meta() {
+            if let Lit::Str(s) = nv.lit {
+                let line = s.value();
+                lines.push(line.strip_prefix(' ').unwrap_or(&line).to_string());
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// `true` if `ty` is `LuaState`, these arguments are not passed by lua.
+pub fn is_lua_state(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "LuaState"),
+        _ => false,
+    }
+}
+
+/// The lua types returned by a function, `lua::Result<T>` is unwrapped and tuples are split.
+/// `()` pushes nothing so it has no return types.
+pub fn return_types(output: &ReturnType) -> Vec<String> {
+    let ty = match output {
+        ReturnType::Default => return Vec::new(),
+        ReturnType::Type(_, ty) => ty.as_ref(),
+    };
+    match ungroup(unwrap_result(ungroup(ty))) {
+        Type::Tuple(tuple) => tuple.elems.iter().map(lua_type).collect(),
+        other => vec![lua_type(other)],
+    }
+}
+
+/// Removes the parentheses and invisible groups around a type, like `(T)`.
+fn ungroup(mut ty: &Type) -> &Type {
+    loop {
+        ty = match ty {
+            Type::Paren(paren) => &paren.elem,
+            Type::Group(group) => &group.elem,
+            _ => return ty,
+        }
+    }
+}
+
+/// Maps a rust type to the name used by the lua annotations.
+/// Types gmrs doesnt know keep their rust name, like user data types.
+pub fn lua_type(ty: &Type) -> String {
+    match ty {
+        Type::Reference(reference) => lua_type(&reference.elem),
+        Type::Paren(paren) => lua_type(&paren.elem),
+        Type::Group(group) => lua_type(&group.elem),
+        // `&[u8]` is a string of bytes
+        Type::Slice(slice) if is_u8(&slice.elem) => "string".to_string(),
+        Type::Slice(slice) => format!("{}[]", lua_type(&slice.elem)),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => "nil".to_string(),
+        Type::Path(path) => {
+            let segment = match path.path.segments.last() {
+                Some(segment) => segment,
+                None => return "any".to_string(),
+            };
+            let args = type_args(&segment.arguments);
+            let ident = segment.ident.to_string();
+            match (ident.as_str(), args.as_slice()) {
+                ("f32" | "f64", []) => "number".to_string(),
+                (
+                    "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize",
+                    [],
+                ) => "integer".to_string(),
+                ("bool", []) => "boolean".to_string(),
+                ("String" | "str" | "CString" | "LuaStr", []) => "string".to_string(),
+                // `Vec<u8>` is a string of bytes
+                ("Vec", [inner]) if is_u8(inner) => "string".to_string(),
+                ("Vec" | "Sequence", [inner]) => format!("{}[]", lua_type(inner)),
+                ("Option", [inner]) => format!("{}?", lua_type(inner)),
+                ("HashMap" | "BTreeMap", [key, value]) => {
+                    format!("table<{}, {}>", lua_type(key), lua_type(value))
+                }
+                ("Result", [inner, ..]) => lua_type(inner),
+                ("UserData", [inner]) => lua_type(inner),
+                ("TableView", []) => "table".to_string(),
+                ("NativeFunc" | "CFunc", []) => "function".to_string(),
+                ("Nil", []) => "nil".to_string(),
+                // any number of values, see `gmrs::stubs::VARARG`
+                ("MultiValue", []) => "...".to_string(),
+                ("OwnedRef" | "ArcRef" | "AtomicRef", []) => "any".to_string(),
+                (other, _) => other.to_string(),
+            }
+        }
+        _ => "any".to_string(),
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("u8"))
+}
+
+/// `T` for `lua::Result<T>`, any other type is returned as is.
+fn unwrap_result(ty: &Type) -> &Type {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Result" {
+                if let Some(inner) = type_args(&segment.arguments).first() {
+                    return inner;
+                }
+            }
+        }
+    }
+    ty
+}
+
+fn type_args(arguments: &PathArguments) -> Vec<&Type> {
+    match arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
@@ -2,6 +2,12 @@
 #define GMOD_ALLOW_DEPRECATED
 #include <Interface.h>
 
+#ifdef _WIN32
+#include <windows.h>
+#else
+#include <dlfcn.h>
+#endif
+
 using namespace GarrysMod::Lua;
 
 static_assert(SPECIAL_GLOB == 0);
@@ -13,6 +19,45 @@ void unused()
     (void)Type::Name;
 }
 
+// Functions that arent part of ILuaBase are looked up in lua_shared, which the game has already loaded.
+static void* find_lua_shared_function(const char* name)
+{
+#ifdef _WIN32
+    HMODULE lua_shared = GetModuleHandleA("lua_shared.dll");
+    if (lua_shared == nullptr)
+        return nullptr;
+    return reinterpret_cast<void*>(GetProcAddress(lua_shared, name));
+#else
+    const char* libraries[] = {"lua_shared.so", "lua_shared_srv.so", "lua_shared_client.so", "lua_shared.dylib"};
+    for (const char* library : libraries)
+    {
+        void* handle = dlopen(library, RTLD_NOW | RTLD_NOLOAD);
+        if (handle == nullptr)
+            continue;
+        void* function = dlsym(handle, name);
+        dlclose(handle);
+        if (function != nullptr)
+            return function;
+    }
+    return dlsym(RTLD_DEFAULT, name);
+#endif
+}
+
+typedef int (*lua_checkstack_t)(lua_State* state, int extra);
+typedef int (*luaL_loadbuffer_t)(lua_State* state, const char* buffer, size_t len, const char* name);
+
+static lua_checkstack_t lua_checkstack_function()
+{
+    static const lua_checkstack_t function = reinterpret_cast<lua_checkstack_t>(find_lua_shared_function("lua_checkstack"));
+    return function;
+}
+
+static luaL_loadbuffer_t luaL_loadbuffer_function()
+{
+    static const luaL_loadbuffer_t function = reinterpret_cast<luaL_loadbuffer_t>(find_lua_shared_function("luaL_loadbuffer"));
+    return function;
+}
+
 extern "C"
 {
     int gmod_bridge_top(lua_State* state)
@@ -79,6 +124,22 @@ extern "C"
     {
         return LUA->Remove(stack_pos);
     }
+    // Returns -1 if lua_checkstack couldnt be found.
+    int gmod_bridge_check_stack(lua_State* state, int extra)
+    {
+        const lua_checkstack_t checkstack = lua_checkstack_function();
+        if (checkstack == nullptr)
+            return -1;
+        return checkstack(state, extra) != 0;
+    }
+    // Returns -1 without pushing anything if luaL_loadbuffer couldnt be found.
+    int gmod_bridge_load_buffer(lua_State* state, const char* buffer, unsigned int len, const char* name)
+    {
+        const luaL_loadbuffer_t loadbuffer = luaL_loadbuffer_function();
+        if (loadbuffer == nullptr)
+            return -1;
+        return loadbuffer(state, buffer, len, name);
+    }
     int gmod_bridge_next(lua_State* state, int stack_pos)
     {
         return LUA->Next(stack_pos);
@@ -97,7 +158,7 @@ extern "C"
     }
     void gmod_bridge_raw_get(lua_State* state, int stack_pos)
     {
-        return LUA->RawSet(stack_pos);
+        return LUA->RawGet(stack_pos);
     }
     void gmod_bridge_raw_set(lua_State* state, int stack_pos)
     {
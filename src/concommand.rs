@@ -0,0 +1,165 @@
+//! Console commands implemented in rust, wraps gmod's `concommand` library.
+//!
+//!```
+//! use gmrs::prelude::*;
+//!
+//! fn register_commands(state: LuaState) -> lua::Result<()> {
+//!     gmrs::concommand::add(
+//!         state,
+//!         "my_module_status",
+//!         |state, cmd| {
+//!             let caller = match &cmd.player {
+//!                 Some(ply) => ply.nick(state)?,
+//!                 None => "console".to_string(),
+//!             };
+//!             gmrs::print!(state, "{} ran {} with {:?}", caller, cmd.name, cmd.args);
+//!             Ok(())
+//!         },
+//!         "Prints the status of my module",
+//!         gmrs::fcvar::NONE,
+//!     )
+//! }
+//!```
+use crate::{
+    internal::{self, StateId},
+    lua::{self, FieldName, LuaSpecial, LuaState, StackGuard},
+    Player,
+};
+use std::{collections::HashSet, sync::Mutex};
+
+lazy_static! {
+    /// Commands added with [add] that havent been removed yet, by state.
+    static ref REGISTERED_COMMANDS: Mutex<HashSet<(StateId, String)>> = Mutex::new(HashSet::new());
+}
+
+/// The arguments a console command was called with.
+#[derive(Debug, Clone)]
+pub struct CommandArgs {
+    /// The player that ran the command, `None` if it was run from the server console.
+    pub player: Option<Player>,
+    /// The name of the command.
+    pub name: String,
+    /// The arguments, split like the engine does.
+    pub args: Vec<String>,
+    /// The arguments as a single string.
+    pub arg_str: String,
+}
+
+/// Equivalent to `concommand.Add(name, handler, nil, help, flags)`.
+/// The command is removed automatically when the module is closed.
+pub fn add<F>(state: LuaState, name: &str, handler: F, help: &str, flags: i32) -> lua::Result<()>
+where
+    F: FnMut(LuaState, CommandArgs) -> lua::Result<()> + Send + 'static,
+{
+    add_command(
+        state,
+        name,
+        handler,
+        None::<fn(&str, &str) -> Vec<String>>,
+        help,
+        flags,
+    )
+}
+
+/// Same as [add] but `autocomplete` is called with the command name and the arguments typed so far
+/// and returns the suggestions shown in the console.
+pub fn add_with_autocomplete<F, A>(
+    state: LuaState,
+    name: &str,
+    handler: F,
+    autocomplete: A,
+    help: &str,
+    flags: i32,
+) -> lua::Result<()>
+where
+    F: FnMut(LuaState, CommandArgs) -> lua::Result<()> + Send + 'static,
+    A: FnMut(&str, &str) -> Vec<String> + Send + 'static,
+{
+    add_command(state, name, handler, Some(autocomplete), help, flags)
+}
+
+/// Equivalent to `concommand.Remove(name)`.
+pub fn remove(state: LuaState, name: &str) -> lua::Result<()> {
+    REGISTERED_COMMANDS
+        .lock()
+        .unwrap()
+        .remove(&(internal::state_id(state), name.to_string()));
+    remove_command(state, name)
+}
+
+/// Removes every command added with [add] in `state`, called automatically by [gmrs::exit].
+pub fn remove_all(state: LuaState) {
+    let current = internal::state_id(state);
+    let mut commands = Vec::new();
+    REGISTERED_COMMANDS.lock().unwrap().retain(|(owner, name)| {
+        if *owner == current {
+            commands.push(name.clone());
+        }
+        *owner != current
+    });
+    for name in commands {
+        let _ = remove_command(state, &name);
+    }
+}
+
+fn add_command<F, A>(
+    state: LuaState,
+    name: &str,
+    mut handler: F,
+    autocomplete: Option<A>,
+    help: &str,
+    flags: i32,
+) -> lua::Result<()>
+where
+    F: FnMut(LuaState, CommandArgs) -> lua::Result<()> + Send + 'static,
+    A: FnMut(&str, &str) -> Vec<String> + Send + 'static,
+{
+    let _guard = StackGuard::new(state);
+    push_concommand_function(state, crate::field!("Add"));
+    lua::pcall_result_with(state, 0, |state| {
+        lua::push(state, name);
+        lua::push_closure(state, move |state| {
+            // concommand callback : function(ply, cmd, args, argStr)
+            let args = CommandArgs {
+                player: lua::get(state, 1).ok(),
+                name: lua::get(state, 2)?,
+                args: lua::get::<lua::Sequence<String>>(state, 3)?.0,
+                arg_str: lua::get::<Option<String>>(state, 4)?.unwrap_or_default(),
+            };
+            handler(state, args)
+        });
+        match autocomplete {
+            Some(mut autocomplete) => lua::push_closure(state, move |state| {
+                // autocomplete callback : function(cmd, argStr, args)
+                let name: String = lua::get(state, 1)?;
+                let arg_str: String = lua::get(state, 2)?;
+                Ok(lua::Sequence(autocomplete(&name, &arg_str)))
+            }),
+            None => lua::push_nil(state),
+        }
+        lua::push(state, help);
+        lua::push(state, flags);
+    })?;
+    REGISTERED_COMMANDS
+        .lock()
+        .unwrap()
+        .insert((internal::state_id(state), name.to_string()));
+    Ok(())
+}
+
+fn remove_command(state: LuaState, name: &str) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
+    push_concommand_function(state, crate::field!("Remove"));
+    lua::pcall_result_with(state, 0, |state| {
+        lua::push(state, name);
+    })
+}
+
+/// Pushes `concommand[name]`.
+fn push_concommand_function(state: LuaState, name: FieldName) {
+    lua::push_special(state, LuaSpecial::Glob);
+    lua::get_field(state, -1, crate::field!("concommand"));
+    lua::get_field(state, -1, name);
+    lua::remove(state, -2);
+    lua::remove(state, -2);
+}
@@ -0,0 +1,165 @@
+//! Thread safe console output.
+//!
+//! The functions in this module can be called from any thread, the output is queued and printed
+//! in batches the next time the hook `Think` is called. When called from the lua thread with
+//! nothing queued the output is printed right away.
+//!
+//!```
+//! use gmrs::prelude::*;
+//!
+//! fn start_worker() {
+//!     std::thread::spawn(|| {
+//!         gmrs::console::print("worker started");
+//!         gmrs::console::msgc(&[(Color::GREEN, "[worker] "), (Color::WHITE, "ready\n")]);
+//!     });
+//! }
+//!```
+use crate::{
+    internal::{self, StateId},
+    lua::LuaState,
+    Color, HudPrint, Player,
+};
+use std::{collections::HashMap, sync::Mutex};
+
+/// Lines queued while the `Think` hook isnt running, for example while the server is
+/// hibernating, are dropped after this limit.
+const MAX_QUEUED_LINES: usize = 4096;
+
+lazy_static! {
+    /// The output queued for each state, `None` is the first state that opened the module.
+    static ref QUEUED_OUTPUT: Mutex<HashMap<Option<StateId>, QueuedOutput>> =
+        Mutex::new(HashMap::new());
+}
+
+#[derive(Default)]
+struct QueuedOutput {
+    lines: Vec<ConsoleLine>,
+    dropped: usize,
+    flush_pending: bool,
+}
+
+/// Owned by a queued flush, clears `flush_pending` when the flush runs or is dropped without
+/// running because the state closed, so the next line schedules a new one.
+struct PendingFlush(Option<StateId>);
+
+impl Drop for PendingFlush {
+    fn drop(&mut self) {
+        if let Some(queued) = QUEUED_OUTPUT.lock().unwrap().get_mut(&self.0) {
+            queued.flush_pending = false;
+        }
+    }
+}
+
+enum ConsoleLine {
+    Print(String),
+    Msg(String),
+    MsgC(Vec<(Color, String)>),
+    ErrorNoHalt(String),
+    PrintTo(Player, HudPrint, String),
+}
+
+impl ConsoleLine {
+    fn output(self, state: LuaState) {
+        match self {
+            Self::Print(message) => crate::print(state, &message),
+            Self::Msg(message) => crate::msg(state, &message),
+            Self::MsgC(parts) => {
+                let parts: Vec<_> = parts
+                    .iter()
+                    .map(|(color, text)| (*color, text.as_str()))
+                    .collect();
+                crate::msgc(state, &parts);
+            }
+            Self::ErrorNoHalt(message) => crate::error_no_halt(state, &message),
+            Self::PrintTo(player, kind, message) => {
+                if player.is_valid(state) {
+                    let _ = crate::print_to(state, &player, kind, &message);
+                }
+            }
+        }
+    }
+}
+
+/// Thread safe version of [crate::print].
+pub fn print(message: &str) {
+    queue_line(None, ConsoleLine::Print(message.to_string()));
+}
+
+/// Thread safe version of [crate::msg].
+pub fn msg(message: &str) {
+    queue_line(None, ConsoleLine::Msg(message.to_string()));
+}
+
+/// Thread safe version of [crate::msgc].
+pub fn msgc(parts: &[(Color, &str)]) {
+    let parts = parts
+        .iter()
+        .map(|(color, text)| (*color, text.to_string()))
+        .collect();
+    queue_line(None, ConsoleLine::MsgC(parts));
+}
+
+/// Thread safe version of [crate::error_no_halt].
+pub fn error_no_halt(message: &str) {
+    queue_line(None, ConsoleLine::ErrorNoHalt(message.to_string()));
+}
+
+/// Thread safe version of [crate::print_to], the message is skipped if the player left.
+/// The message is printed by the state the player was taken from.
+pub fn print_to(player: &Player, kind: HudPrint, message: &str) {
+    queue_line(
+        Some(player.state()),
+        ConsoleLine::PrintTo(player.clone(), kind, message.to_string()),
+    );
+}
+
+/// Queues `line` to be printed by `target`.
+fn queue_line(target: Option<StateId>, line: ConsoleLine) {
+    let current = internal::get_lua_state().filter(|state| match target {
+        Some(target) => internal::state_id(*state) == target,
+        None => true,
+    });
+    let mut queues = QUEUED_OUTPUT.lock().unwrap();
+    let queued = queues.entry(target).or_default();
+    if let (Some(state), true) = (current, queued.lines.is_empty()) {
+        drop(queues);
+        line.output(state);
+        return;
+    }
+    if queued.lines.len() < MAX_QUEUED_LINES {
+        queued.lines.push(line);
+    } else {
+        queued.dropped += 1;
+    }
+    if queued.flush_pending {
+        return;
+    }
+    // one flush prints the whole batch
+    queued.flush_pending = true;
+    // queued without the lock, dropping the flush if the state is closed takes it again
+    drop(queues);
+    let pending = PendingFlush(target);
+    internal::queue_execute_on(target, move |state| flush(state, pending));
+}
+
+fn flush(state: LuaState, pending: PendingFlush) {
+    let target = pending.0;
+    // lines queued from now on schedule another flush
+    drop(pending);
+    let (lines, dropped) = match QUEUED_OUTPUT.lock().unwrap().get_mut(&target) {
+        Some(queued) => (
+            std::mem::take(&mut queued.lines),
+            std::mem::take(&mut queued.dropped),
+        ),
+        None => return,
+    };
+    for line in lines {
+        line.output(state);
+    }
+    if dropped > 0 {
+        crate::print(
+            state,
+            &format!("[gmrs] {} console lines were dropped", dropped),
+        );
+    }
+}
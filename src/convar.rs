@@ -0,0 +1,329 @@
+//! Typed console variables, wraps `CreateConVar` and `cvars.AddChangeCallback`.
+//!
+//! The value of a [ConVar] is cached on the rust side and kept up to date by a change callback,
+//! so it can be read from any thread without going through [crate::remote_execute].
+//!
+//!```
+//! use gmrs::prelude::*;
+//! use gmrs::convar::{self, ConVar};
+//!
+//! fn start_worker(state: LuaState) -> lua::Result<()> {
+//!     let interval: ConVar<f64> = convar::create(
+//!         state,
+//!         "my_module_interval",
+//!         5.0,
+//!         gmrs::fcvar::ARCHIVE,
+//!         "Seconds between updates",
+//!     )?;
+//!     interval.on_change(|state, old, new| {
+//!         gmrs::print!(state, "interval changed from {} to {}", old, new);
+//!     });
+//!     std::thread::spawn(move || loop {
+//!         std::thread::sleep(std::time::Duration::from_secs_f64(interval.get()));
+//!     });
+//!     Ok(())
+//! }
+//!```
+use crate::{
+    internal::{self, StateId},
+    lua::{self, FieldName, LuaSpecial, LuaState, StackGuard, ToStack},
+};
+use crossbeam::atomic::AtomicCell;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+
+lazy_static! {
+    /// States, convar names and identifiers of the change callbacks installed by [create].
+    static ref REGISTERED_CALLBACKS: Mutex<Vec<(StateId, String, String)>> = Mutex::new(Vec::new());
+}
+
+/// A type that can be stored in a [ConVar].
+/// Conversions from strings behave like the `ConVar:Get*` functions, invalid values become zero.
+pub trait ConVarValue: Sized + Send + Sync + 'static {
+    /// Where the cached value is stored, lock free for numbers and bools.
+    type Cache: Send + Sync;
+
+    fn from_convar_string(value: &str) -> Self;
+    fn to_convar_string(&self) -> String;
+
+    fn new_cache(value: Self) -> Self::Cache;
+    fn load(cache: &Self::Cache) -> Self;
+    fn store(cache: &Self::Cache, value: Self);
+}
+
+macro_rules! impl_number_convar_value {
+    ($ty:ty) => {
+        impl ConVarValue for $ty {
+            type Cache = AtomicCell<$ty>;
+
+            fn from_convar_string(value: &str) -> Self {
+                value.trim().parse::<f64>().unwrap_or(0.0) as $ty
+            }
+
+            fn to_convar_string(&self) -> String {
+                self.to_string()
+            }
+
+            fn new_cache(value: Self) -> Self::Cache {
+                AtomicCell::new(value)
+            }
+
+            fn load(cache: &Self::Cache) -> Self {
+                cache.load()
+            }
+
+            fn store(cache: &Self::Cache, value: Self) {
+                cache.store(value)
+            }
+        }
+    };
+}
+
+impl_number_convar_value!(i32);
+impl_number_convar_value!(i64);
+impl_number_convar_value!(u32);
+impl_number_convar_value!(f32);
+impl_number_convar_value!(f64);
+
+impl ConVarValue for bool {
+    type Cache = AtomicCell<bool>;
+
+    fn from_convar_string(value: &str) -> Self {
+        i32::from_convar_string(value) != 0
+    }
+
+    fn to_convar_string(&self) -> String {
+        if *self { "1" } else { "0" }.to_string()
+    }
+
+    fn new_cache(value: Self) -> Self::Cache {
+        AtomicCell::new(value)
+    }
+
+    fn load(cache: &Self::Cache) -> Self {
+        cache.load()
+    }
+
+    fn store(cache: &Self::Cache, value: Self) {
+        cache.store(value)
+    }
+}
+
+/// Strings are cached behind a lock.
+impl ConVarValue for String {
+    type Cache = RwLock<String>;
+
+    fn from_convar_string(value: &str) -> Self {
+        value.to_string()
+    }
+
+    fn to_convar_string(&self) -> String {
+        self.clone()
+    }
+
+    fn new_cache(value: Self) -> Self::Cache {
+        RwLock::new(value)
+    }
+
+    fn load(cache: &Self::Cache) -> Self {
+        cache.read().unwrap().clone()
+    }
+
+    fn store(cache: &Self::Cache, value: Self) {
+        *cache.write().unwrap() = value;
+    }
+}
+
+type ChangeCallback<T> = Arc<dyn Fn(LuaState, &T, &T) + Send + Sync>;
+
+struct ConVarInner<T: ConVarValue> {
+    name: String,
+    cache: T::Cache,
+    callbacks: Mutex<Vec<ChangeCallback<T>>>,
+}
+
+/// Handle to a console variable, cheap to clone and can be sent to other threads.
+pub struct ConVar<T: ConVarValue>(Arc<ConVarInner<T>>);
+
+impl<T: ConVarValue> ConVar<T> {
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Returns the cached value, can be called from any thread.
+    pub fn get(&self) -> T {
+        T::load(&self.0.cache)
+    }
+
+    /// Changes the value of the convar.
+    /// The value is read back afterwards, the game could have clamped it.
+    pub fn set(&self, state: LuaState, value: T) -> lua::Result<()> {
+        let _guard = StackGuard::new(state);
+        push_convar(state, &self.0.name)?;
+        lua::get_field(state, -1, crate::field!("SetString"));
+        lua::push_copy(state, -2);
+        lua::push(state, value.to_convar_string());
+        lua::pcall_result(state, 2, 0)?;
+        let value = get_string(state, -1)?;
+        T::store(&self.0.cache, T::from_convar_string(&value));
+        Ok(())
+    }
+
+    /// Adds a function that is called with the old and new value every time the convar changes.
+    pub fn on_change<F>(&self, callback: F)
+    where
+        F: Fn(LuaState, &T, &T) + Send + Sync + 'static,
+    {
+        self.0.callbacks.lock().unwrap().push(Arc::new(callback));
+    }
+
+    fn changed(&self, state: LuaState, old: &str, new: &str) {
+        T::store(&self.0.cache, T::from_convar_string(new));
+        let old = T::from_convar_string(old);
+        let new = T::from_convar_string(new);
+        // the lock isnt held while calling them, a callback could set the convar again
+        let callbacks = self.0.callbacks.lock().unwrap().clone();
+        for callback in callbacks {
+            callback(state, &old, &new);
+        }
+    }
+}
+
+impl<T: ConVarValue> Clone for ConVar<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: ConVarValue + fmt::Debug> fmt::Debug for ConVar<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConVar")
+            .field("name", &self.0.name)
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+/// Equivalent to `CreateConVar(name, default, flags, help)`, check [crate::fcvar] for the flags.
+/// If the convar already exists its current value is used.
+pub fn create<T: ConVarValue>(
+    state: LuaState,
+    name: &str,
+    default: T,
+    flags: i32,
+    help: &str,
+) -> lua::Result<ConVar<T>> {
+    let _guard = StackGuard::new(state);
+    push_global(state, crate::field!("CreateConVar"));
+    lua::pcall_result_with(state, 1, |state| {
+        lua::push(state, name);
+        lua::push(state, default.to_convar_string());
+        lua::push(state, flags);
+        lua::push(state, help);
+    })?;
+    let value = get_string(state, -1)?;
+
+    let convar = ConVar(Arc::new(ConVarInner {
+        name: name.to_string(),
+        cache: T::new_cache(T::from_convar_string(&value)),
+        callbacks: Mutex::new(Vec::new()),
+    }));
+    let callback_convar = convar.clone();
+    add_change_callback(
+        state,
+        name,
+        lua::closure(move |state| {
+            // change callback : function(name, old, new)
+            let old: String = lua::get(state, 2)?;
+            let new: String = lua::get(state, 3)?;
+            callback_convar.changed(state, &old, &new);
+            Ok(())
+        }),
+    )?;
+    Ok(convar)
+}
+
+/// Removes the change callbacks installed by [create] in `state`, called automatically by
+/// [gmrs::exit]. The convars themselves cant be removed.
+pub fn remove_all(state: LuaState) {
+    let current = internal::state_id(state);
+    let mut callbacks = Vec::new();
+    REGISTERED_CALLBACKS
+        .lock()
+        .unwrap()
+        .retain(|(owner, name, identifier)| {
+            if *owner == current {
+                callbacks.push((name.clone(), identifier.clone()));
+            }
+            *owner != current
+        });
+    for (name, identifier) in callbacks {
+        let _guard = StackGuard::new(state);
+        push_cvars_function(state, crate::field!("RemoveChangeCallback"));
+        let _ = lua::pcall_result_with(state, 0, |state| {
+            lua::push(state, name);
+            lua::push(state, identifier);
+        });
+    }
+}
+
+fn add_change_callback<C: ToStack>(state: LuaState, name: &str, callback: C) -> lua::Result<()> {
+    // a convar only keeps one callback per identifier, so every handle gets its own
+    static NEXT_IDENTIFIER: AtomicU32 = AtomicU32::new(0);
+    let identifier = format!(
+        "gmrs_convar_{}",
+        NEXT_IDENTIFIER.fetch_add(1, Ordering::Relaxed)
+    );
+
+    let _guard = StackGuard::new(state);
+    push_cvars_function(state, crate::field!("AddChangeCallback"));
+    lua::pcall_result_with(state, 0, |state| {
+        lua::push(state, name);
+        lua::push(state, callback);
+        lua::push(state, identifier.as_str());
+    })?;
+    REGISTERED_CALLBACKS.lock().unwrap().push((
+        internal::state_id(state),
+        name.to_string(),
+        identifier,
+    ));
+    Ok(())
+}
+
+/// Returns `convar:GetString()` of the convar at `stack_pos`.
+fn get_string(state: LuaState, stack_pos: i32) -> lua::Result<String> {
+    let _guard = StackGuard::new(state);
+    let stack_pos = lua::rel_to_abs(state, stack_pos);
+    lua::get_field(state, stack_pos, crate::field!("GetString"));
+    lua::push_copy(state, stack_pos);
+    lua::pcall_result(state, 1, 1)?;
+    lua::get(state, -1)
+}
+
+/// Pushes `GetConVar(name)`.
+fn push_convar(state: LuaState, name: &str) -> lua::Result<()> {
+    push_global(state, crate::field!("GetConVar"));
+    lua::pcall_result_with(state, 1, |state| {
+        lua::push(state, name);
+    })
+}
+
+fn push_global(state: LuaState, name: FieldName) {
+    lua::push_special(state, LuaSpecial::Glob);
+    lua::get_field(state, -1, name);
+    lua::remove(state, -2);
+}
+
+/// Pushes `cvars[name]`.
+fn push_cvars_function(state: LuaState, name: FieldName) {
+    lua::push_special(state, LuaSpecial::Glob);
+    lua::get_field(state, -1, crate::field!("cvars"));
+    lua::get_field(state, -1, name);
+    lua::remove(state, -2);
+    lua::remove(state, -2);
+}
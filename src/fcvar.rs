@@ -0,0 +1,28 @@
+//! `FCVAR_*` flags used when creating console commands and convars.
+//! Check <https://wiki.facepunch.com/gmod/Enums/FCVAR>.
+
+pub const NONE: i32 = 0;
+pub const UNREGISTERED: i32 = 1;
+pub const DEVELOPMENTONLY: i32 = 2;
+pub const GAMEDLL: i32 = 4;
+pub const CLIENTDLL: i32 = 8;
+pub const HIDDEN: i32 = 16;
+pub const PROTECTED: i32 = 32;
+pub const SPONLY: i32 = 64;
+pub const ARCHIVE: i32 = 128;
+pub const NOTIFY: i32 = 256;
+pub const USERINFO: i32 = 512;
+pub const PRINTABLEONLY: i32 = 1024;
+pub const UNLOGGED: i32 = 2048;
+pub const NEVER_AS_STRING: i32 = 4096;
+pub const REPLICATED: i32 = 8192;
+pub const CHEAT: i32 = 16384;
+pub const DEMO: i32 = 65536;
+pub const DONTRECORD: i32 = 131072;
+pub const LUA_CLIENT: i32 = 262144;
+pub const LUA_SERVER: i32 = 524288;
+pub const NOT_CONNECTED: i32 = 4194304;
+pub const ARCHIVE_XBOX: i32 = 16777216;
+pub const SERVER_CAN_EXECUTE: i32 = 268435456;
+pub const SERVER_CANNOT_QUERY: i32 = 536870912;
+pub const CLIENTCMD_CAN_EXECUTE: i32 = 1073741824;
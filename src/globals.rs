@@ -0,0 +1,150 @@
+//! Access to globals and nested fields with paths like `util.JSONToTable`.
+//!
+//! Every segment of a path but the last must be a table. Functions called with [call_global] are
+//! cached by path, if a function is replaced after being called the old one keeps being used
+//! until [clear_cache] is called. Each state that opened the module has its own cache, it is
+//! cleared when the module exits. Functions called in other states are resolved every time.
+//!
+//!```
+//! use gmrs::prelude::*;
+//!
+//! fn map_info(state: LuaState) -> lua::Result<(String, f64)> {
+//!     let map: String = gmrs::call_global(state, "game.GetMap", ())?;
+//!     let max_players: f64 = gmrs::call_global(state, "game.MaxPlayers", ())?;
+//!     gmrs::set_global_path(state, "my_module.config.map", map.as_str())?;
+//!     Ok((map, max_players))
+//! }
+//!```
+use crate::{
+    internal::{self, StateId},
+    lua::{self, FromStack, LuaSpecial, LuaState, LuaType, StackGuard, ToStack, MULT_RET},
+    OwnedRef,
+};
+use std::{collections::HashMap, sync::Mutex};
+
+lazy_static! {
+    /// The functions resolved by [call_global], by state and path.
+    static ref FUNCTION_CACHE: Mutex<HashMap<StateId, HashMap<String, OwnedRef>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Returns the value at `path` converted to `T`.
+/// The last segment can be nil, use `Option<T>` for values that might not exist.
+pub fn get_global<T: FromStack>(state: LuaState, path: &str) -> lua::Result<T> {
+    let _guard = StackGuard::new(state);
+    push_path(state, path, false)?;
+    lua::get(state, -1)
+}
+
+/// Sets the value at `path`, nil segments are replaced with new tables.
+pub fn set_global_path<T: ToStack>(state: LuaState, path: &str, value: T) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
+    let (parent, key) = match path.rfind('.') {
+        Some(index) => (Some(&path[..index]), &path[index + 1..]),
+        None => (None, path),
+    };
+    match parent {
+        Some(parent) => push_path(state, parent, true)?,
+        None => lua::push_special(state, LuaSpecial::Glob),
+    }
+    if key.is_empty() {
+        return Err(invalid_path(path));
+    }
+    lua::try_push(state, value)?;
+    lua::set_field(state, -2, key)
+}
+
+/// Calls the function at `path` with `args` and returns its results.
+pub fn call_global<A, R>(state: LuaState, path: &str, args: A) -> lua::Result<R>
+where
+    A: ToStack,
+    R: FromStack,
+{
+    let guard = StackGuard::new(state);
+    push_function(state, path)?;
+    lua::pcall_result_with(state, MULT_RET, |state| {
+        lua::push(state, args);
+    })?;
+    lua::get(state, guard.top() + 1)
+}
+
+/// Forgets the functions cached by [call_global] in `state`.
+pub fn clear_cache(state: LuaState) {
+    let functions = FUNCTION_CACHE
+        .lock()
+        .unwrap()
+        .remove(&internal::state_id(state));
+    // the references are freed without holding the lock
+    drop(functions);
+}
+
+/// Pushes the function at `path`, from the cache if it was already resolved.
+/// Only the states with the module open are cached, the others are never cleared.
+fn push_function(state: LuaState, path: &str) -> lua::Result<()> {
+    // the lock isnt held while resolving, metamethods could call back into rust
+    let id = internal::state_id(state);
+    {
+        let cache = FUNCTION_CACHE.lock().unwrap();
+        if let Some(function) = cache.get(&id).and_then(|functions| functions.get(path)) {
+            lua::reserve_stack(state, 1)?;
+            lua::push(state, function);
+            return Ok(());
+        }
+    }
+    push_path(state, path, false)?;
+    match lua::get_type(state, -1) {
+        LuaType::Function if internal::is_open(id) => {
+            let function = OwnedRef::new(state, -1);
+            FUNCTION_CACHE
+                .lock()
+                .unwrap()
+                .entry(id)
+                .or_default()
+                .insert(path.to_string(), function);
+        }
+        LuaType::Nil => {
+            return Err(lua::Error::InvalidPath {
+                path: path.to_string(),
+                segment: path.to_string(),
+                found: LuaType::Nil,
+            })
+        }
+        // functions of states without the module and tables with a __call metamethod
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Pushes the value at `path`, if `create` is true nil segments are replaced with new tables.
+/// Values pushed before failing are left on the stack.
+fn push_path(state: LuaState, path: &str, create: bool) -> lua::Result<()> {
+    let segments: Vec<&str> = path.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(invalid_path(path));
+    }
+    lua::reserve_stack(state, 3)?;
+    lua::push_special(state, LuaSpecial::Glob);
+    for (index, segment) in segments.iter().copied().enumerate() {
+        let found = lua::get_type(state, -1);
+        if found != LuaType::Table {
+            return Err(lua::Error::InvalidPath {
+                path: path.to_string(),
+                segment: segments[..index].join("."),
+                found,
+            });
+        }
+        lua::get_field(state, -1, segment)?;
+        if create && lua::is_type(state, -1, LuaType::Nil) {
+            lua::pop(state, 1);
+            lua::create_table(state);
+            lua::push_copy(state, -1);
+            lua::set_field(state, -3, segment)?;
+        }
+        lua::remove(state, -2);
+    }
+    Ok(())
+}
+
+fn invalid_path(path: &str) -> lua::Error {
+    lua::Error::CustomMessage(format!("invalid global path '{}'", path))
+}
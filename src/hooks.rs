@@ -0,0 +1,245 @@
+//! Typed wrappers over gmod's `hook` library.
+//!
+//!```
+//! use gmrs::prelude::*;
+//!
+//! fn register_hooks(state: LuaState) -> lua::Result<()> {
+//!     // the state can be one of the arguments, like in `gmrs::function`
+//!     let callback = |state: LuaState, ply: Player, text: String, _team: bool| {
+//!         if text == "!ping" {
+//!             let _ = ply.chat_print(state, "pong");
+//!             return Some(String::new());
+//!         }
+//!         None
+//!     };
+//!     // keep the hook until the module is closed
+//!     gmrs::hooks::add(state, "PlayerSay", "my_module", callback)?.detach();
+//!     Ok(())
+//! }
+//!```
+use crate::{
+    internal::{self, StateId},
+    lua::{self, FieldName, FromStack, LuaSpecial, LuaState, StackGuard, ToStack, MULT_RET},
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+lazy_static! {
+    /// Hooks added with [add] that havent been removed yet, by state, event and id.
+    /// The generation tells apart a hook from the one that replaced it with the same id.
+    static ref REGISTERED_HOOKS: Mutex<HashMap<(StateId, String, String), u64>> =
+        Mutex::new(HashMap::new());
+}
+
+/// A rust function that can be used as a hook callback.
+/// The arguments are converted using [FromStack] and the result is returned to `hook.Run`.
+pub trait HookCallback<Args, R>: Send + 'static {
+    fn call(&mut self, state: LuaState) -> lua::Result<R>;
+}
+
+macro_rules! impl_hook_callback {
+    ($($arg:ident),*) => {
+        impl<Func, R, $($arg),*> HookCallback<($($arg,)*), R> for Func
+        where
+            Func: FnMut($($arg),*) -> R + Send + 'static,
+            $($arg: FromStack,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables, unused_assignments)]
+            fn call(&mut self, state: LuaState) -> lua::Result<R> {
+                let mut stack_pos = 1;
+                $(
+                    let ($arg, used) = $arg::from_stack(state, stack_pos)?;
+                    stack_pos += used;
+                )*
+                Ok(self($($arg),*))
+            }
+        }
+    };
+}
+
+impl_hook_callback!();
+impl_hook_callback!(A);
+impl_hook_callback!(A, B);
+impl_hook_callback!(A, B, C);
+impl_hook_callback!(A, B, C, D);
+impl_hook_callback!(A, B, C, D, E);
+impl_hook_callback!(A, B, C, D, E, F);
+
+/// Handle to a hook added with [add], the hook is removed when the handle is dropped.
+#[derive(Debug)]
+#[must_use = "the hook is removed when the handle is dropped"]
+pub struct HookHandle {
+    event: String,
+    id: String,
+    state: StateId,
+    generation: u64,
+    detached: bool,
+}
+
+impl HookHandle {
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Keeps the hook after the handle is dropped, it is still removed when the module is closed.
+    pub fn detach(mut self) {
+        self.detached = true;
+    }
+}
+
+impl Drop for HookHandle {
+    fn drop(&mut self) {
+        if self.detached {
+            return;
+        }
+        let key = (
+            self.state,
+            std::mem::take(&mut self.event),
+            std::mem::take(&mut self.id),
+        );
+        {
+            let mut hooks = REGISTERED_HOOKS.lock().unwrap();
+            if hooks.get(&key) != Some(&self.generation) {
+                // already removed by `remove_all` or replaced by another hook with the same id
+                return;
+            }
+            hooks.remove(&key);
+        }
+        let (_, event, id) = key;
+        match internal::get_lua_state() {
+            Some(state) if internal::state_id(state) == self.state => {
+                let _ = remove(state, &event, &id);
+            }
+            _ => internal::queue_execute_on(Some(self.state), move |state| {
+                let _ = remove(state, &event, &id);
+            }),
+        }
+    }
+}
+
+/// Adds a rust function as the hook `id` of `event`.
+/// The arguments of `func` are converted from the hook arguments and its result is returned to
+/// `hook.Run`, return `()` or `None` to let the other hooks run.
+pub fn add<Args, R, F>(
+    state: LuaState,
+    event: &str,
+    id: &str,
+    mut func: F,
+) -> lua::Result<HookHandle>
+where
+    F: HookCallback<Args, R>,
+    R: ToStack + Send + 'static,
+{
+    add_value(
+        state,
+        event,
+        id,
+        lua::closure(move |state| func.call(state)),
+    )?;
+    let state = internal::state_id(state);
+    let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+    REGISTERED_HOOKS
+        .lock()
+        .unwrap()
+        .insert((state, event.to_string(), id.to_string()), generation);
+    Ok(HookHandle {
+        event: event.to_string(),
+        id: id.to_string(),
+        state,
+        generation,
+        detached: false,
+    })
+}
+
+/// Equivalent to `hook.Add(event, id, value)`.
+pub fn add_value<T: ToStack>(state: LuaState, event: &str, id: &str, value: T) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
+    push_hook_function(state, crate::field!("Add"));
+    lua::pcall_result_with(state, 0, |state| {
+        lua::push(state, event);
+        lua::push(state, id);
+        lua::push(state, value);
+    })
+}
+
+/// Equivalent to `hook.Remove(event, id)`.
+pub fn remove(state: LuaState, event: &str, id: &str) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
+    push_hook_function(state, crate::field!("Remove"));
+    lua::pcall_result_with(state, 0, |state| {
+        lua::push(state, event);
+        lua::push(state, id);
+    })
+}
+
+/// Removes every hook added with [add] in `state`, called automatically by [gmrs::exit].
+pub fn remove_all(state: LuaState) {
+    let current = internal::state_id(state);
+    let mut hooks = Vec::new();
+    REGISTERED_HOOKS
+        .lock()
+        .unwrap()
+        .retain(|(owner, event, id), _| {
+            if *owner == current {
+                hooks.push((event.clone(), id.clone()));
+            }
+            *owner != current
+        });
+    for (event, id) in hooks {
+        let _ = remove(state, &event, &id);
+    }
+}
+
+/// Equivalent to `hook.Run(event, args...)`, `args` can be a tuple to pass multiple arguments.
+/// Returns the results of the hook converted to `R`, `None` or `()` if you dont care.
+///
+///```
+///# use gmrs::prelude::*;
+/// fn can_do_thing(state: LuaState, ply: &Player) -> lua::Result<bool> {
+///     let allowed: Option<bool> = gmrs::hooks::run(state, "CanDoThing", (ply, "thing"))?;
+///     Ok(allowed.unwrap_or(true))
+/// }
+///```
+pub fn run<R, A>(state: LuaState, event: &str, args: A) -> lua::Result<R>
+where
+    R: FromStack,
+    A: ToStack,
+{
+    run_with(state, event, |state| {
+        lua::push(state, args);
+    })
+}
+
+/// Same as [run] but `func` should push the arguments to the stack.
+pub fn run_with<R, F>(state: LuaState, event: &str, func: F) -> lua::Result<R>
+where
+    R: FromStack,
+    F: FnOnce(LuaState),
+{
+    let guard = StackGuard::new(state);
+    push_hook_function(state, crate::field!("Run"));
+    lua::pcall_result_with(state, MULT_RET, |state| {
+        lua::push(state, event);
+        func(state);
+    })?;
+    lua::get(state, guard.top() + 1)
+}
+
+/// Pushes `hook[name]`.
+fn push_hook_function(state: LuaState, name: FieldName) {
+    lua::push_special(state, LuaSpecial::Glob);
+    lua::get_field(state, -1, crate::field!("hook"));
+    lua::get_field(state, -1, name);
+    lua::remove(state, -2);
+    lua::remove(state, -2);
+}
@@ -1,13 +1,40 @@
 use crossbeam::channel::{self, Receiver, Sender};
-use std::{cell::Cell, time::Instant};
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
 
-use crate::lua::{self, LuaState, LuaStateRaw};
+use crate::lua::{self, LuaSpecial, LuaState, LuaStateRaw, StackGuard};
+
+/// Registry field that stores the [StateId] of a state.
+const STATE_ID_FIELD: lua::FieldName = crate::field!("gmrs_state_id");
 
 std::thread_local!(static CURRENT_LUA_STATE_RAW: Cell<LuaStateRaw> = Cell::new(std::ptr::null_mut()));
+std::thread_local! {
+    /// The id of the current state, looked up the first time it is needed during a call.
+    static CURRENT_STATE_ID: Cell<Option<StateId>> = const { Cell::new(None) };
+}
+static NEXT_STATE_ID: AtomicU64 = AtomicU64::new(1);
 lazy_static! {
     static ref CREATION_TIME: Instant = Instant::now();
-    static ref INTERNAL_CHANNELS: (Sender<InternalMessage>, Receiver<InternalMessage>) =
-        channel::unbounded();
+    /// The queues of the states that have the module open, ordered by when they opened it.
+    static ref INTERNAL_CHANNELS: Mutex<BTreeMap<StateId, Sender<InternalMessage>>> =
+        Mutex::new(BTreeMap::new());
+}
+
+/// Identifies a lua state, like the client, server or menu state.
+/// Returned by [state_id] and used to choose the state with [remote_execute_on].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StateId(u64);
+
+impl StateId {
+    /// Never assigned to a state, used by references that dont point to anything.
+    pub(crate) const NONE: StateId = StateId(0);
 }
 
 enum InternalMessage {
@@ -29,6 +56,7 @@ pub fn get_lua_state() -> Option<LuaState> {
 /// This function should only be called with a valid LuaStateRaw that is given to us when lua call's us.
 pub unsafe fn set_lua_state_raw(raw: LuaStateRaw) {
     CURRENT_LUA_STATE_RAW.with(|c| c.set(raw));
+    CURRENT_STATE_ID.with(|c| c.set(None));
 }
 
 /// Sets the current thread's lua state to null, probably dont need call this
@@ -36,21 +64,61 @@ pub fn unset_lua_state_raw() {
     unsafe { set_lua_state_raw(std::ptr::null_mut()) }
 }
 
+/// Returns the id of `state`, assigning a new one the first time it is called for a state.
+/// Coroutines share the id of the state that created them.
+/// The id of the current state is only read from the registry once per call.
+pub fn state_id(state: LuaState) -> StateId {
+    if CURRENT_LUA_STATE_RAW.with(|c| c.get()) != state.ptr() {
+        return lookup_state_id(state);
+    }
+    CURRENT_STATE_ID.with(|cached| {
+        let id = cached.get().unwrap_or_else(|| lookup_state_id(state));
+        cached.set(Some(id));
+        id
+    })
+}
+
+/// The id of the current state, see [get_lua_state].
+pub(crate) fn current_state_id() -> Option<StateId> {
+    get_lua_state().map(state_id)
+}
+
+fn lookup_state_id(state: LuaState) -> StateId {
+    let _guard = StackGuard::new(state);
+    lua::push_special(state, LuaSpecial::Reg);
+    lua::get_field(state, -1, STATE_ID_FIELD);
+    if let Ok(Some(id)) = lua::get::<Option<f64>>(state, -1) {
+        return StateId(id as u64);
+    }
+    let id = StateId(NEXT_STATE_ID.fetch_add(1, Ordering::Relaxed));
+    lua::push(state, id.0 as f64);
+    lua::set_field(state, -3, STATE_ID_FIELD);
+    id
+}
+
 /// # Safety
 /// Should only be called once from the module open function.
 /// It will add a hook that handles internal events.
 /// It is automatically called when using [gmrs::entry]
 pub unsafe fn install_hook(state: LuaState) {
-    let hook_name = internal_hook_name_from_time(*CREATION_TIME);
-    crate::hook_add(
+    let id = state_id(state);
+    let (sender, receiver) = channel::unbounded();
+    INTERNAL_CHANNELS.lock().unwrap().insert(id, sender);
+    let hook_name = internal_hook_name(id);
+    let result = crate::hook_add(
         state,
         "Think",
         &hook_name,
-        crate::lua::closure(|state| {
-            let receiver = INTERNAL_CHANNELS.1.clone();
-            internal_think_loop(state, receiver)
-        }),
+        crate::lua::closure(move |state| internal_think_loop(state, &receiver)),
     );
+    if let Err(e) = result {
+        crate::print!(state, "[gmrs] Failed to install the internal hook: {}", e);
+    }
+}
+
+/// `true` if the module is open in the state `id`, between [install_hook] and [uninstall_hook].
+pub(crate) fn is_open(id: StateId) -> bool {
+    INTERNAL_CHANNELS.lock().unwrap().contains_key(&id)
 }
 
 /// # Safety
@@ -58,38 +126,118 @@ pub unsafe fn install_hook(state: LuaState) {
 /// It will add a hook that handles internal events.
 /// It is automatically called when using [gmrs::exit]
 pub unsafe fn uninstall_hook(state: LuaState) {
-    let hook_name = internal_hook_name_from_time(*CREATION_TIME);
-    crate::hook_remove(state, "Think", &hook_name);
+    let id = state_id(state);
+    // the messages still queued are dropped with the hook, the id is kept in the registry so the
+    // references dropped while closing are still freed in this state
+    INTERNAL_CHANNELS.lock().unwrap().remove(&id);
+    let _ = crate::hook_remove(state, "Think", &internal_hook_name(id));
 }
 
-fn send_internal_message(msg: InternalMessage) {
-    let _ = INTERNAL_CHANNELS.0.send(msg);
+/// Prints a warning if the stack doesnt have `expected` values after a native function returns.
+/// Called by [gmrs::function] when compiled with `debug_assertions`.
+pub fn check_stack_balance(state: LuaState, function: &str, expected: i32) {
+    let actual = lua::top(state);
+    if actual != expected {
+        crate::print(
+            state,
+            &format!(
+                "[gmrs] '{}' left an unexpected number of values on the stack, expected {} found {}",
+                function, expected, actual
+            ),
+        );
+    }
+}
+
+/// Sends `msg` to the queue of `target`, or of the first state that opened the module if `None`.
+/// The message is dropped if the module isnt open in the state.
+fn send_internal_message(target: Option<StateId>, msg: InternalMessage) {
+    // cloned so the lock isnt held while sending
+    let sender = {
+        let channels = INTERNAL_CHANNELS.lock().unwrap();
+        match target {
+            Some(id) => channels.get(&id).cloned(),
+            None => channels.values().next().cloned(),
+        }
+    };
+    if let Some(sender) = sender {
+        let _ = sender.send(msg);
+    }
 }
 
-/// Queues a reference to be freed later.
-pub fn remote_reference_free(reference: i32) {
-    send_internal_message(InternalMessage::ReferenceFree(reference));
+/// Queues a reference of `target` to be freed later.
+/// The reference is leaked if the module isnt open in `target`.
+pub fn remote_reference_free(target: StateId, reference: i32) {
+    send_internal_message(Some(target), InternalMessage::ReferenceFree(reference));
+}
+
+/// Queues a function to be executed by `target` the next time the hook `Think` is called,
+/// doesnt wait for it.
+pub(crate) fn queue_execute_on<F>(target: Option<StateId>, func: F)
+where
+    F: FnOnce(LuaState) + Send + 'static,
+{
+    send_internal_message(target, InternalMessage::RemoteExecute(Box::new(func)));
 }
 
 /// Executes a function using the lua state.
 /// This function will block until the hook `Think` is called and we have access to the [LuaState].
+/// The function runs in the first state that opened the module, use [remote_execute_on] when the
+/// module is open in more than one state.
+///
+/// # Panics
+/// If no state has the module open or it is closed before running `func`.
 pub fn remote_execute<F, R>(func: F) -> R
 where
     R: Send + 'static,
     F: FnOnce(LuaState) -> R + Send + 'static,
 {
-    let (tx, rx) = channel::bounded(0);
-    let msg = InternalMessage::RemoteExecute(Box::new(move |state| {
-        let result = func(state);
-        // this should never fail
-        tx.send(result).unwrap();
-    }));
-    send_internal_message(msg);
-    // this should never fail either
-    rx.recv().unwrap()
+    execute(None, func)
+}
+
+/// Like [remote_execute] but `func` runs in the state `target`.
+///
+///```no_run
+///# use gmrs::prelude::*;
+/// #[gmrs::entry]
+/// fn open(state: LuaState) {
+///     let id = gmrs::state_id(state);
+///     std::thread::spawn(move || {
+///         gmrs::remote_execute_on(id, |state| gmrs::print(state, "hello from a thread"));
+///     });
+/// }
+///```
+///
+/// # Panics
+/// If `target` is closed before running `func`.
+pub fn remote_execute_on<F, R>(target: StateId, func: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce(LuaState) -> R + Send + 'static,
+{
+    execute(Some(target), func)
 }
 
 pub fn remote_try_execute<F, R>(func: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce(LuaState) -> lua::Result<R> + Send + 'static,
+{
+    try_execute(None, func)
+}
+
+/// Like [remote_try_execute] but `func` runs in the state `target`.
+///
+/// # Panics
+/// If `target` is closed before running `func` or `func` returns an error.
+pub fn remote_try_execute_on<F, R>(target: StateId, func: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce(LuaState) -> lua::Result<R> + Send + 'static,
+{
+    try_execute(Some(target), func)
+}
+
+fn try_execute<F, R>(target: Option<StateId>, func: F) -> R
 where
     R: Send + 'static,
     F: FnOnce(LuaState) -> lua::Result<R> + Send + 'static,
@@ -101,16 +249,32 @@ where
         tx.send(result).unwrap();
         Ok(())
     }));
-    send_internal_message(msg);
-    // this should never fail either
+    send_internal_message(target, msg);
+    // fails if the message was dropped
     rx.recv().unwrap()
 }
 
-fn internal_hook_name_from_time(time: Instant) -> String {
-    format!("gmrs_internal_hook_{:?}", time)
+fn execute<F, R>(target: Option<StateId>, func: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce(LuaState) -> R + Send + 'static,
+{
+    let (tx, rx) = channel::bounded(0);
+    let msg = InternalMessage::RemoteExecute(Box::new(move |state| {
+        let result = func(state);
+        // this should never fail
+        tx.send(result).unwrap();
+    }));
+    send_internal_message(target, msg);
+    rx.recv()
+        .expect("the lua state was closed before executing the function")
+}
+
+fn internal_hook_name(id: StateId) -> String {
+    format!("gmrs_internal_hook_{:?}_{}", *CREATION_TIME, id.0)
 }
 
-fn internal_think_loop(state: LuaState, receiver: Receiver<InternalMessage>) -> lua::Result<()> {
+fn internal_think_loop(state: LuaState, receiver: &Receiver<InternalMessage>) -> lua::Result<()> {
     while let Ok(msg) = receiver.try_recv() {
         match msg {
             InternalMessage::ReferenceFree(reference) => lua::reference_free(state, reference),
@@ -1,31 +1,52 @@
 #[macro_use]
 extern crate lazy_static;
 
+pub mod concommand;
+pub mod console;
+pub mod convar;
+pub mod fcvar;
+pub mod globals;
+pub mod hooks;
 pub mod internal;
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub mod logging;
 pub mod lua;
+pub mod net;
 pub mod refs;
+pub mod scripts;
+pub mod stubs;
+#[cfg(feature = "test-backend")]
+pub mod test_backend;
+#[cfg(feature = "test-backend")]
+pub mod testing;
+pub mod types;
 
-pub use gmrs_impl::{entry, exit, function, raw_function};
-pub use internal::{get_lua_state, remote_execute, remote_try_execute};
+pub use globals::{call_global, get_global, set_global_path};
+pub use gmrs_impl::{entry, exit, function, function_info, module, raw_function};
+pub use internal::{
+    get_lua_state, remote_execute, remote_execute_on, remote_try_execute, remote_try_execute_on,
+    state_id, StateId,
+};
 pub use refs::{ArcRef, AtomicRef, OwnedRef};
+pub use types::{Angle, Color, Entity, Player, Vector};
 
-use lua::{LuaSpecial, LuaState, ToStack};
+use lua::{FromStack, LuaSpecial, LuaState, StackGuard, ToStack};
 
 pub mod prelude {
     pub use super::lua::{
         self, FromStack, FromTable, LuaSpecial, LuaState, LuaStateRaw, MetatableBuilder,
-        NativeFunc, TableView, ToStack, UserData, UserType,
+        NativeFunc, StackGuard, TableView, ToStack, UserData, UserType,
     };
-    pub use super::{ArcRef, AtomicRef, OwnedRef};
+    pub use super::{Angle, ArcRef, AtomicRef, Color, Entity, OwnedRef, Player, Vector};
 }
 
 /// Prints the message using gmod's `print` function, the message should show up on the console.
 pub fn print(state: LuaState, message: &str) {
+    let _guard = StackGuard::new(state);
     lua::push_special(state, LuaSpecial::Glob);
-    lua::get_field(state, -1, "print");
+    lua::get_field(state, -1, field!("print"));
     lua::push_string(state, message);
     unsafe { lua::call(state, 1, 0) };
-    lua::pop(state, 1);
 }
 
 #[macro_export]
@@ -36,55 +57,98 @@ macro_rules! print {
     };
 }
 
-/// Equivalent to `hook.Add(event, id, callback)`.
-pub fn hook_add<T: ToStack>(state: LuaState, event: &str, id: &str, val: T) {
+/// Equivalent to `Msg(message)`, prints without a new line.
+pub fn msg(state: LuaState, message: &str) {
+    let _ = call_global_with(state, field!("Msg"), |state| {
+        lua::push(state, message);
+    });
+}
+
+/// Equivalent to `MsgC(color, text, ...)`, prints every part with its color and without a new line.
+///
+///```
+///# use gmrs::prelude::*;
+/// fn report(state: LuaState, failed: u32) {
+///     gmrs::msgc(
+///         state,
+///         &[(Color::WHITE, "tests failed: "), (Color::RED, &format!("{}\n", failed))],
+///     );
+/// }
+///```
+pub fn msgc(state: LuaState, parts: &[(Color, &str)]) {
+    let _ = call_global_with(state, field!("MsgC"), |state| {
+        for (color, text) in parts {
+            lua::push(state, *color);
+            lua::push(state, *text);
+        }
+    });
+}
+
+/// Equivalent to `ErrorNoHalt(message)`, prints the message as an error without stopping execution.
+pub fn error_no_halt(state: LuaState, message: &str) {
+    let _ = call_global_with(state, field!("ErrorNoHalt"), |state| {
+        lua::push(state, message);
+    });
+}
+
+/// Where a message sent with [print_to] shows up, same as the `HUD_PRINT*` enums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudPrint {
+    Notify = 1,
+    Console = 2,
+    Talk = 3,
+    Center = 4,
+}
+
+/// Equivalent to `player:PrintMessage(kind, message)`, only available on the server.
+pub fn print_to(
+    state: LuaState,
+    player: &Player,
+    kind: HudPrint,
+    message: &str,
+) -> lua::Result<()> {
+    player.call_method(state, "PrintMessage", |state| {
+        lua::push(state, kind as i32);
+        lua::push(state, message);
+    })
+}
+
+/// Calls the global function `name`, `args` should push the arguments.
+fn call_global_with<F: FnOnce(LuaState)>(
+    state: LuaState,
+    name: lua::FieldName,
+    args: F,
+) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
     lua::push_special(state, LuaSpecial::Glob);
-    lua::get_field(state, -1, "hook");
-    lua::get_field(state, -1, "Add");
-    lua::push(state, event);
-    lua::push(state, id);
-    lua::push(state, val);
-    let status = lua::pcall(state, 3, 0);
-    assert!(status, "Calling hook.Add cant fail");
+    lua::get_field(state, -1, name);
+    lua::pcall_result_with(state, 0, args)
+}
+
+/// Equivalent to `hook.Add(event, id, callback)`. Check [hooks::add] for typed rust callbacks.
+pub fn hook_add<T: ToStack>(state: LuaState, event: &str, id: &str, val: T) -> lua::Result<()> {
+    hooks::add_value(state, event, id, val)
 }
 
 /// Equivalent to `hook.Remove(event, id)`.
-pub fn hook_remove(state: LuaState, event: &str, id: &str) {
-    lua::push_special(state, LuaSpecial::Glob);
-    lua::get_field(state, -1, "hook");
-    lua::get_field(state, -1, "Remove");
-    lua::push(state, event);
-    lua::push(state, id);
-    let status = lua::pcall(state, 2, 0);
-    assert!(status, "Calling hook.Remove cant fail");
+pub fn hook_remove(state: LuaState, event: &str, id: &str) -> lua::Result<()> {
+    hooks::remove(state, event, id)
 }
 
-/// Runs hook with name `name` by calling `hook.Run`.  
+/// Runs hook with name `name` by calling `hook.Run` and returns its results.  
 /// `func` should push the parameters to the stack.
-pub fn hook_run<F>(state: LuaState, name: &str, func: F)
+pub fn hook_run<R, F>(state: LuaState, name: &str, func: F) -> lua::Result<R>
 where
+    R: FromStack,
     F: FnOnce(LuaState),
 {
-    lua::push_special(state, LuaSpecial::Glob);
-    lua::get_field(state, -1, "hook");
-    lua::get_field(state, -1, "Run");
-    lua::push(state, name);
-    let size_before_args = lua::top(state);
-    func(state);
-    let size_after_args = lua::top(state);
-    let arg_count = size_after_args - size_before_args;
-    assert!(
-        arg_count >= 0,
-        "Dont pop values in the function that is supposed to push values"
-    );
-    let status = lua::pcall(state, arg_count, 0);
-    assert!(status, "Calling hook.Run cant fail"); // right ?
+    hooks::run_with(state, name, func)
 }
 
-/// Sets a value on the global table.
-pub fn set_global<T: ToStack>(state: LuaState, key: &str, value: T) {
+/// Sets a value on the global table, fails if `key` contains a NUL byte.
+pub fn set_global<T: ToStack>(state: LuaState, key: &str, value: T) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
     lua::push_special(state, LuaSpecial::Glob);
     lua::push(state, value);
-    lua::set_field(state, -2, key);
-    lua::pop(state, 1);
+    lua::set_field(state, -2, key)
 }
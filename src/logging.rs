@@ -0,0 +1,361 @@
+//! Backends for the `log` and `tracing` crates that print to the game console.
+//!
+//! Enabled with the `log` and `tracing` features. Records can be logged from any thread, they are
+//! printed with [crate::console] and optionally mirrored to a file in `garrysmod/data`.
+//!
+//!```
+//! use gmrs::logging::{self, Level};
+//!
+//! fn setup_logging() -> gmrs::lua::Result<()> {
+//!     logging::builder()
+//!         .level(Level::Debug)
+//!         .file("my_module")
+//!         .init()
+//! }
+//!```
+use crate::{lua, Color};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Log files are created in this directory by default, relative to the game's working directory.
+const LOG_DIRECTORY: &str = "garrysmod/data";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+
+    /// The color used for the level name on the console.
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Error => Color::RED,
+            Self::Warn => Color::rgb(255, 200, 0),
+            Self::Info => Color::GREEN,
+            Self::Debug => Color::rgb(0, 200, 255),
+            Self::Trace => Color::rgb(160, 160, 160),
+        }
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Configures the logger, created with [builder].
+#[derive(Debug, Clone)]
+pub struct LoggerBuilder {
+    level: Level,
+    file: Option<String>,
+    directory: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+}
+
+impl LoggerBuilder {
+    /// Records less severe than `level` are ignored, defaults to [Level::Info].
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Mirrors the records to `<name>.log` in [directory](Self::directory), `name` cant contain
+    /// path separators or `..`.
+    pub fn file(mut self, name: &str) -> Self {
+        self.file = Some(name.to_string());
+        self
+    }
+
+    /// The directory of the log file, defaults to `garrysmod/data`.
+    pub fn directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.directory = directory.into();
+        self
+    }
+
+    /// The log file is rotated once it reaches `size` bytes, defaults to 5MB.
+    pub fn max_file_size(mut self, size: u64) -> Self {
+        self.max_file_size = size;
+        self
+    }
+
+    /// Number of rotated files kept besides the current one, defaults to 3.
+    pub fn max_files(mut self, count: usize) -> Self {
+        self.max_files = count;
+        self
+    }
+
+    /// Installs the logger for every enabled backend, fails if another logger was already set.
+    pub fn init(self) -> lua::Result<()> {
+        let file = match &self.file {
+            Some(name) => Some(Mutex::new(RotatingFile::open(
+                &self.directory,
+                name,
+                self.max_file_size,
+                self.max_files,
+            )?)),
+            None => None,
+        };
+        let output = Arc::new(Output {
+            level: self.level,
+            file,
+        });
+        #[cfg(feature = "log")]
+        log_backend::init(output.clone())?;
+        #[cfg(feature = "tracing")]
+        tracing_backend::init(output.clone())?;
+        Ok(())
+    }
+}
+
+pub fn builder() -> LoggerBuilder {
+    LoggerBuilder {
+        level: Level::Info,
+        file: None,
+        directory: PathBuf::from(LOG_DIRECTORY),
+        max_file_size: 5 * 1024 * 1024,
+        max_files: 3,
+    }
+}
+
+/// Same as `builder().level(level).init()`.
+pub fn init(level: Level) -> lua::Result<()> {
+    builder().level(level).init()
+}
+
+/// Where the records of every backend end up.
+struct Output {
+    level: Level,
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl Output {
+    fn enabled(&self, level: Level) -> bool {
+        level <= self.level
+    }
+
+    fn write(&self, level: Level, target: &str, message: &str) {
+        let timestamp = format_timestamp(SystemTime::now());
+        crate::console::msgc(&[
+            (Color::rgb(160, 160, 160), &format!("{} ", timestamp)),
+            (level.color(), &format!("{:<5} ", level.name())),
+            (Color::WHITE, &format!("{}: {}\n", target, message)),
+        ]);
+        if let Some(file) = &self.file {
+            let line = format!(
+                "{} {:<5} {}: {}\n",
+                timestamp,
+                level.name(),
+                target,
+                message
+            );
+            file.lock().unwrap().write(line.as_bytes());
+        }
+    }
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    fn open(directory: &Path, name: &str, max_size: u64, max_files: usize) -> lua::Result<Self> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+            return Err(format!("invalid log file name '{}'", name).into());
+        }
+        let path = directory.join(format!("{}.log", name));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+            max_files,
+        })
+    }
+
+    /// Write errors are ignored, there is nowhere to report them.
+    fn write(&mut self, data: &[u8]) {
+        if self.size + data.len() as u64 > self.max_size && self.size > 0 {
+            let _ = self.rotate();
+        }
+        if self.file.write_all(data).is_ok() {
+            self.size += data.len() as u64;
+        }
+    }
+
+    /// Renames `name.log` to `name.1.log`, `name.1.log` to `name.2.log` and so on.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let rotated = |index: usize| self.path.with_extension(format!("{}.log", index));
+        if self.max_files > 0 {
+            let _ = fs::remove_file(rotated(self.max_files));
+            for index in (1..self.max_files).rev() {
+                let _ = fs::rename(rotated(index), rotated(index + 1));
+            }
+            fs::rename(&self.path, rotated(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Formats the time of day as `HH:MM:SS.mmm` in UTC.
+fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs() % (24 * 60 * 60);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(feature = "log")]
+mod log_backend {
+    use super::{Level, Output};
+    use crate::lua;
+    use std::sync::Arc;
+
+    struct Logger(Arc<Output>);
+
+    impl log::Log for Logger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            self.0.enabled(convert_level(metadata.level()))
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.0.write(
+                    convert_level(record.level()),
+                    record.target(),
+                    &record.args().to_string(),
+                );
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn convert_level(level: log::Level) -> Level {
+        match level {
+            log::Level::Error => Level::Error,
+            log::Level::Warn => Level::Warn,
+            log::Level::Info => Level::Info,
+            log::Level::Debug => Level::Debug,
+            log::Level::Trace => Level::Trace,
+        }
+    }
+
+    pub(super) fn init(output: Arc<Output>) -> lua::Result<()> {
+        let max_level = match output.level {
+            Level::Error => log::LevelFilter::Error,
+            Level::Warn => log::LevelFilter::Warn,
+            Level::Info => log::LevelFilter::Info,
+            Level::Debug => log::LevelFilter::Debug,
+            Level::Trace => log::LevelFilter::Trace,
+        };
+        log::set_boxed_logger(Box::new(Logger(output)))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_backend {
+    use super::{Level, Output};
+    use crate::lua;
+    use std::{fmt::Write, sync::Arc};
+    use tracing::{
+        field::{Field, Visit},
+        Event, Metadata, Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        Layer,
+    };
+
+    struct ConsoleLayer(Arc<Output>);
+
+    impl<S: Subscriber> Layer<S> for ConsoleLayer {
+        fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+            self.0.enabled(convert_level(metadata.level()))
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            let metadata = event.metadata();
+            visitor.message.push_str(&visitor.fields);
+            self.0.write(
+                convert_level(metadata.level()),
+                metadata.target(),
+                &visitor.message,
+            );
+        }
+    }
+
+    /// Formats the `message` field followed by the other fields as `name=value`.
+    #[derive(Default)]
+    struct MessageVisitor {
+        message: String,
+        fields: String,
+    }
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                let _ = write!(self.message, "{:?}", value);
+            } else {
+                let _ = write!(self.fields, " {}={:?}", field.name(), value);
+            }
+        }
+    }
+
+    fn convert_level(level: &tracing::Level) -> Level {
+        match *level {
+            tracing::Level::ERROR => Level::Error,
+            tracing::Level::WARN => Level::Warn,
+            tracing::Level::INFO => Level::Info,
+            tracing::Level::DEBUG => Level::Debug,
+            tracing::Level::TRACE => Level::Trace,
+        }
+    }
+
+    pub(super) fn init(output: Arc<Output>) -> lua::Result<()> {
+        let subscriber = tracing_subscriber::registry().with(ConsoleLayer(output));
+        tracing::subscriber::set_global_default(subscriber)?;
+        Ok(())
+    }
+}
@@ -30,6 +30,13 @@ extern "C" {
     pub fn gmod_bridge_raw_equal(state: LuaStateRaw, a: i32, b: i32) -> i32;
     pub fn gmod_bridge_insert(state: LuaStateRaw, stack_pos: i32);
     pub fn gmod_bridge_remove(state: LuaStateRaw, stack_pos: i32);
+    pub fn gmod_bridge_check_stack(state: LuaStateRaw, extra: i32) -> i32;
+    pub fn gmod_bridge_load_buffer(
+        state: LuaStateRaw,
+        buffer: *const std::os::raw::c_char,
+        len: u32,
+        name: *const std::os::raw::c_char,
+    ) -> i32;
     pub fn gmod_bridge_next(state: LuaStateRaw, stack_pos: i32) -> i32;
     pub fn gmod_bridge_throw_error(state: LuaStateRaw, error: *const std::os::raw::c_char);
     pub fn gmod_bridge_check_type(state: LuaStateRaw, stack_pos: i32, ty: i32);
@@ -44,7 +51,7 @@ extern "C" {
     ) -> *const std::os::raw::c_char;
     pub fn gmod_bridge_get_number(state: LuaStateRaw, stack_pos: i32) -> f64;
     pub fn gmod_bridge_get_bool(state: LuaStateRaw, stack_pos: i32) -> bool;
-    pub fn gmod_bridge_get_c_function(state: LuaStateRaw, stack_pos: i32) -> CFunc;
+    pub fn gmod_bridge_get_c_function(state: LuaStateRaw, stack_pos: i32) -> Option<CFunc>;
     pub fn gmod_bridge_get_vector(state: LuaStateRaw, stack_pos: i32, vector: *mut f32);
     pub fn gmod_bridge_get_angle(state: LuaStateRaw, stack_pos: i32, angle: *mut f32);
     pub fn gmod_bridge_push_nil(state: LuaStateRaw);
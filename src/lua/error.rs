@@ -1,12 +1,121 @@
+use super::{LuaState, ToStack};
+use crate::refs::OwnedRef;
+
 #[derive(Debug)]
 pub enum Error {
     CustomMessage(String),
     CustomBytes(Vec<u8>),
-    Generic(Box<dyn std::error::Error + Send>),
+    Generic(Box<dyn std::error::Error + Send + Sync>),
+    /// An arbitrary lua value used as the error object, like a table with `code` and `message`
+    /// fields. When thrown the value is passed to lua as is, also when wrapped with
+    /// [ResultExt::context]. `message` is the `tostring` of the value when the error was created.
+    Value {
+        value: OwnedRef,
+        message: String,
+    },
+    /// An argument passed to a native function was invalid, formatted like lua's `luaL_argerror`,
+    /// `bad argument #2 to 'send' (number expected, got nil)`.
+    BadArgument {
+        position: i32,
+        function: &'static str,
+        error: Box<Error>,
+    },
+    /// A chunk failed to compile, the message from lua includes the chunk name and line.
+    Syntax(String),
+    /// A path like `util.JSONToTable` could not be resolved because `segment`, the path up to and
+    /// including the value being indexed, is not a table. When the function called is nil
+    /// `segment` is the whole path.
+    InvalidPath {
+        path: String,
+        segment: String,
+        found: super::LuaType,
+    },
+    /// The stack could not grow to fit the given number of values.
+    StackOverflow(i32),
+    /// A function that isnt part of `ILuaBase` couldnt be found in lua_shared.
+    MissingFunction(&'static str),
+    /// An error with additional context, created with [ResultExt::context].
+    Context {
+        context: String,
+        error: Box<Error>,
+    },
 }
-impl<E: std::error::Error + Send + 'static> From<E> for Error {
+impl Error {
+    /// Creates an error whose error object is `value`.
+    pub fn value<T: ToStack>(state: LuaState, value: T) -> Self {
+        super::push(state, value);
+        let error = Self::from_stack_value(state, -1);
+        super::pop(state, 1);
+        error
+    }
+
+    /// Creates an error whose error object is the value at `stack_pos`.
+    pub fn from_stack_value(state: LuaState, stack_pos: i32) -> Self {
+        Self::Value {
+            value: OwnedRef::new(state, stack_pos),
+            message: describe_value(state, stack_pos),
+        }
+    }
+
+    /// The error object of an [Error::Value], also when it was wrapped with context.
+    pub fn as_value(&self) -> Option<&OwnedRef> {
+        match self {
+            Self::Value { value, .. } => Some(value),
+            Self::Context { error, .. } => error.as_value(),
+            _ => None,
+        }
+    }
+
+    /// Creates an error for the argument at `position` of `function`.
+    /// Used by [gmrs::function] when an argument fails to convert.
+    pub fn bad_argument<E: Into<Error>>(position: i32, function: &'static str, error: E) -> Self {
+        Self::BadArgument {
+            position,
+            function,
+            error: Box::new(error.into()),
+        }
+    }
+}
+/// Any error that can be boxed can be converted, this includes `String`, `&str`, `anyhow::Error`
+/// and errors that are not `Send` or `Sync`. Only the messages of the error and its sources are
+/// kept, [Error::from_boxed] keeps the error itself.
+impl<E: Into<Box<dyn std::error::Error>>> From<E> for Error {
     fn from(e: E) -> Self {
-        Self::Generic(Box::new(e))
+        let err: Box<dyn std::error::Error> = e.into();
+        Self::Generic(Box::new(ErrorMessage::new(err.as_ref())))
+    }
+}
+impl Error {
+    /// Creates an [Error::Generic] from an already boxed error, which `?` can't convert.
+    pub fn from_boxed(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Self::Generic(err)
+    }
+}
+
+/// The message of an error and of its sources, used for errors that may not be `Send`.
+#[derive(Debug)]
+struct ErrorMessage {
+    message: String,
+    source: Option<Box<ErrorMessage>>,
+}
+impl ErrorMessage {
+    fn new(err: &dyn std::error::Error) -> Self {
+        Self {
+            message: err.to_string(),
+            source: err.source().map(|source| Box::new(Self::new(source))),
+        }
+    }
+}
+impl std::fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+impl std::error::Error for ErrorMessage {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
     }
 }
 impl std::fmt::Display for Error {
@@ -17,9 +126,164 @@ impl std::fmt::Display for Error {
                 Ok(string) => write!(f, "{}", string),
                 Err(_) => write!(f, "Error::CustomBytes, failed to convert to string"),
             },
-            Self::Generic(err) => write!(f, "{}", err),
+            Self::Generic(err) => {
+                write!(f, "{}", err)?;
+                let mut source = err.source();
+                while let Some(err) = source {
+                    write!(f, ": {}", err)?;
+                    source = err.source();
+                }
+                Ok(())
+            }
+            Self::Value { message, .. } => write!(f, "{}", message),
+            Self::BadArgument {
+                position,
+                function,
+                error,
+            } => write!(
+                f,
+                "bad argument #{} to '{}' ({})",
+                position, function, error
+            ),
+            Self::Syntax(msg) => write!(f, "{}", msg),
+            Self::InvalidPath {
+                path,
+                segment,
+                found,
+            } => write!(
+                f,
+                "failed to resolve '{}', '{}' is {}",
+                path, segment, found
+            ),
+            Self::StackOverflow(extra) => {
+                write!(f, "stack overflow, cant grow the stack by {} values", extra)
+            }
+            Self::MissingFunction(name) => write!(f, "'{}' couldnt be found in lua_shared", name),
+            Self::Context { context, error } => write!(f, "{}: {}", context, error),
+        }
+    }
+}
+/// Pushes the error object, the message for string errors or the value itself for [Error::Value],
+/// even if it was wrapped with context.
+impl ToStack for Error {
+    fn push(self, state: LuaState) -> i32 {
+        if let Some(value) = self.as_value() {
+            super::push(state, value);
+            return 1;
+        }
+        match self {
+            Self::CustomBytes(msg) => super::push_bytes(state, &msg),
+            other => super::push_string(state, &format!("{}", other)),
+        }
+        1
+    }
+}
+
+/// Returns `tostring(value)` of the value at `stack_pos`, or its type if `tostring` fails.
+fn describe_value(state: LuaState, stack_pos: i32) -> String {
+    let _guard = super::StackGuard::new(state);
+    let stack_pos = super::rel_to_abs(state, stack_pos);
+    let ty = super::get_type(state, stack_pos);
+    if super::reserve_stack(state, 2).is_err() {
+        return format!("{} error object", ty);
+    }
+    super::push_special(state, super::LuaSpecial::Glob);
+    super::get_field(state, -1, crate::field!("tostring"));
+    super::push_copy(state, stack_pos);
+    // not pcall_result, a failing __tostring would describe its own error object
+    if !super::pcall(state, 1, 1) {
+        return format!("{} error object", ty);
+    }
+    super::get::<String>(state, -1).unwrap_or_else(|_| format!("{} error object", ty))
+}
+
+/// Keeps the source chain of [Error::Generic] and the context of [Error::Context], the other errors
+/// are stored as a [StdError] and can be taken back with [Error::from_anyhow].
+#[cfg(feature = "anyhow")]
+impl From<Error> for anyhow::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Generic(err) => anyhow::anyhow!(err),
+            Error::Context { context, error } => anyhow::Error::from(*error).context(context),
+            other => anyhow::Error::new(StdError(other)),
+        }
+    }
+}
+
+/// An [Error] that implements `std::error::Error`, used to store it in an `anyhow::Error`.
+///
+///```
+///# use gmrs::prelude::*;
+/// fn value_error(state: LuaState) -> anyhow::Result<()> {
+///     Err(lua::Error::value(state, 42.0).into())
+/// }
+///
+/// fn call(state: LuaState) -> lua::Result<()> {
+///     // the error object is still the value 42
+///     value_error(state).map_err(lua::Error::from_anyhow)
+/// }
+///```
+#[cfg(feature = "anyhow")]
+#[derive(Debug)]
+pub struct StdError(pub Error);
+
+#[cfg(feature = "anyhow")]
+impl std::fmt::Display for StdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl std::error::Error for StdError {}
+
+#[cfg(feature = "anyhow")]
+impl Error {
+    /// Converts an `anyhow::Error`, errors created from a lua [Error] are given back as is.
+    /// `?` boxes the error instead, keeping only its messages.
+    pub fn from_anyhow(err: anyhow::Error) -> Self {
+        match err.downcast::<StdError>() {
+            Ok(wrapped) => wrapped.0,
+            Err(err) => err.into(),
         }
     }
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Adds context to errors as they bubble up.
+///
+///```
+///# use gmrs::prelude::*;
+/// use gmrs::lua::ResultExt;
+///
+/// fn load_config(path: &str) -> lua::Result<Vec<u8>> {
+///     std::fs::read(path).context("while loading config")
+/// }
+///```
+pub trait ResultExt<T> {
+    /// Wraps the error with `context`.
+    fn context<C: std::fmt::Display>(self, context: C) -> Result<T>;
+
+    /// Wraps the error with the context returned by `func`, only called if there is an error.
+    fn with_context<C, F>(self, func: F) -> Result<T>
+    where
+        C: std::fmt::Display,
+        F: FnOnce() -> C;
+}
+impl<T, E: Into<Error>> ResultExt<T> for std::result::Result<T, E> {
+    fn context<C: std::fmt::Display>(self, context: C) -> Result<T> {
+        self.with_context(|| context)
+    }
+
+    fn with_context<C, F>(self, func: F) -> Result<T>
+    where
+        C: std::fmt::Display,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|e| Error::Context {
+            context: func().to_string(),
+            error: Box::new(e.into()),
+        })
+    }
+}
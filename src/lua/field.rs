@@ -0,0 +1,110 @@
+use super::{Error, Result};
+use std::ffi::{CStr, CString};
+
+/// Names shorter than this are nul terminated on the stack instead of allocating.
+const STACK_NAME_SIZE: usize = 64;
+
+/// A nul terminated field name checked at compile time, created with [field!](crate::field).
+/// Passing it to [get_field](super::get_field) and [set_field](super::set_field) doesnt allocate
+/// and cant fail.
+///
+///```
+///# use gmrs::prelude::*;
+/// const HOOK: lua::FieldName = gmrs::field!("hook");
+///
+/// fn push_hook_add(state: LuaState) {
+///     lua::push_special(state, LuaSpecial::Glob);
+///     lua::get_field(state, -1, HOOK);
+///     lua::get_field(state, -1, gmrs::field!("Add"));
+/// }
+///```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldName(&'static CStr);
+
+impl FieldName {
+    /// Panics if `bytes` is not nul terminated or contains other NUL bytes, a compile error when
+    /// used in a constant.
+    pub const fn from_bytes_with_nul(bytes: &'static [u8]) -> Self {
+        match CStr::from_bytes_with_nul(bytes) {
+            Ok(name) => Self(name),
+            Err(_) => panic!("field names must end with a NUL byte and not contain any other"),
+        }
+    }
+
+    pub const fn from_c_str(name: &'static CStr) -> Self {
+        Self(name)
+    }
+
+    pub fn as_c_str(&self) -> &'static CStr {
+        self.0
+    }
+}
+
+/// Creates a [FieldName] from a string literal at compile time.
+#[macro_export]
+macro_rules! field {
+    ($name:literal) => {{
+        const NAME: $crate::lua::FieldName =
+            $crate::lua::FieldName::from_bytes_with_nul(concat!($name, "\0").as_bytes());
+        NAME
+    }};
+}
+
+/// Names accepted by [get_field](super::get_field) and [set_field](super::set_field).
+/// [FieldName] and `&CStr` are already nul terminated so nothing is returned, strings are checked
+/// and return an error if they contain a NUL byte.
+pub trait FieldKey {
+    type Output;
+
+    /// Calls `func` with the nul terminated name.
+    fn with_c_str<F: FnOnce(&CStr)>(self, func: F) -> Self::Output;
+}
+
+impl FieldKey for FieldName {
+    type Output = ();
+
+    fn with_c_str<F: FnOnce(&CStr)>(self, func: F) {
+        func(self.0)
+    }
+}
+
+impl FieldKey for &CStr {
+    type Output = ();
+
+    fn with_c_str<F: FnOnce(&CStr)>(self, func: F) {
+        func(self)
+    }
+}
+
+impl FieldKey for &str {
+    type Output = Result<()>;
+
+    fn with_c_str<F: FnOnce(&CStr)>(self, func: F) -> Result<()> {
+        let bytes = self.as_bytes();
+        if bytes.contains(&0) {
+            return Err(Error::CustomMessage(format!(
+                "field name {:?} contains a NUL byte",
+                self
+            )));
+        }
+        if bytes.len() < STACK_NAME_SIZE {
+            let mut buffer = [0u8; STACK_NAME_SIZE];
+            buffer[..bytes.len()].copy_from_slice(bytes);
+            // the name has no NUL bytes and the buffer is zeroed after it
+            let name = unsafe { CStr::from_bytes_with_nul_unchecked(&buffer[..=bytes.len()]) };
+            func(name);
+        } else {
+            let name = unsafe { CString::from_vec_unchecked(bytes.to_vec()) };
+            func(&name);
+        }
+        Ok(())
+    }
+}
+
+impl FieldKey for &String {
+    type Output = Result<()>;
+
+    fn with_c_str<F: FnOnce(&CStr)>(self, func: F) -> Result<()> {
+        self.as_str().with_c_str(func)
+    }
+}
@@ -0,0 +1,47 @@
+use super::LuaState;
+
+/// Records the size of the stack when created and pops anything above it when dropped.
+///
+///```
+///# use gmrs::prelude::*;
+/// fn get_map(state: LuaState) -> lua::Result<String> {
+///     let _guard = lua::StackGuard::new(state);
+///     lua::push_special(state, LuaSpecial::Glob);
+///     lua::get_field(state, -1, gmrs::field!("game"));
+///     lua::get_field(state, -1, gmrs::field!("GetMap"));
+///     lua::pcall_result(state, 0, 1)?;
+///     // the global table, `game` and the map name are popped here.
+///     lua::get(state, -1)
+/// }
+///```
+#[derive(Debug)]
+pub struct StackGuard {
+    state: LuaState,
+    top: i32,
+}
+impl StackGuard {
+    pub fn new(state: LuaState) -> Self {
+        Self {
+            state,
+            top: super::top(state),
+        }
+    }
+
+    /// The size of the stack when the guard was created.
+    pub fn top(&self) -> i32 {
+        self.top
+    }
+
+    /// Consumes the guard without restoring the stack.
+    pub fn forget(self) {
+        std::mem::forget(self)
+    }
+}
+impl Drop for StackGuard {
+    fn drop(&mut self) {
+        let extra = super::top(self.state) - self.top;
+        if extra > 0 {
+            super::pop(self.state, extra as u32);
+        }
+    }
+}
@@ -7,21 +7,31 @@
 //! - <https://github.com/Facepunch/gmod-module-base/blob/development>
 mod bridge;
 mod error;
+mod field;
+mod guard;
 mod stack;
+mod string;
 mod table;
 mod user_data;
 
 use std::{mem::MaybeUninit, str::Utf8Error};
 
 pub use bridge::{CFunc, LuaStateRaw, MULT_RET};
-pub use error::{Error, Result};
-pub use stack::{FromStack, FromStackError, ToStack};
-pub use table::{FromTable, TableView};
+#[cfg(feature = "anyhow")]
+pub use error::StdError;
+pub use error::{Error, Result, ResultExt};
+pub use field::{FieldKey, FieldName};
+pub use guard::StackGuard;
+pub use stack::{FromStack, FromStackError, FromStackRef, MultiValue, ToStack};
+pub use string::LuaStr;
+pub use table::{FromTable, Sequence, TableView};
 pub use user_data::{MetatableBuilder, UserData, UserType};
 
 pub const NIL: Nil = Nil;
 
 const LUA_GLOBALSINDEX: i32 = -10002;
+// https://www.lua.org/source/5.1/lua.h.html#LUA_MINSTACK
+const LUA_MINSTACK: i32 = 20;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct LuaState(LuaStateRaw);
@@ -123,6 +133,31 @@ impl LuaType {
             unknown => LuaType::Other(unknown),
         }
     }
+
+    /// Returns the name of the type as returned by lua's `type` function.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LuaType::None => "no value",
+            LuaType::Nil => "nil",
+            LuaType::Bool => "boolean",
+            LuaType::LightUserData => "lightuserdata",
+            LuaType::Number => "number",
+            LuaType::String => "string",
+            LuaType::Table => "table",
+            LuaType::Function => "function",
+            LuaType::UserData => "userdata",
+            LuaType::Thread => "thread",
+            LuaType::Entity => "Entity",
+            LuaType::Vector => "Vector",
+            LuaType::Angle => "Angle",
+            LuaType::Other(_) => "unknown",
+        }
+    }
+}
+impl std::fmt::Display for LuaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
 /// Pops `count` values from the stack.
@@ -149,6 +184,25 @@ pub fn expect_type(state: LuaState, stack_pos: i32, ty: LuaType) -> Result<(), F
     }
 }
 
+/// Makes sure the stack has space for at least `extra` more values, growing it if needed.
+/// Returns `false` if the stack cant grow.
+pub fn check_stack(state: LuaState, extra: i32) -> bool {
+    reserve_stack(state, extra).is_ok()
+}
+
+/// Same as [check_stack] but returns [Error::StackOverflow] if the stack cant grow.
+/// If `lua_checkstack` couldnt be found in lua_shared only the `LUA_MINSTACK` (20) slots lua
+/// guarantees to a C function are assumed to be free, bigger reserves return
+/// [Error::MissingFunction].
+pub fn reserve_stack(state: LuaState, extra: i32) -> Result<()> {
+    match unsafe { bridge::gmod_bridge_check_stack(state.ptr(), extra) } {
+        1 => Ok(()),
+        0 => Err(Error::StackOverflow(extra)),
+        _ if extra <= LUA_MINSTACK => Ok(()),
+        _ => Err(Error::MissingFunction("lua_checkstack")),
+    }
+}
+
 /// Converts a **relative** stack index to an **absolute** stack index.
 /// If the index is absolute it does nothing.
 pub fn rel_to_abs(state: LuaState, mut stack_pos: i32) -> i32 {
@@ -159,11 +213,49 @@ pub fn rel_to_abs(state: LuaState, mut stack_pos: i32) -> i32 {
     stack_pos
 }
 
+/// Moves the value at the top of the stack into `stack_pos`, shifting up the elements above it.
+pub fn insert(state: LuaState, stack_pos: i32) {
+    unsafe { bridge::gmod_bridge_insert(state.ptr(), stack_pos) }
+}
+
+/// Removes the value at `stack_pos`, shifting down the elements above it.
+pub fn remove(state: LuaState, stack_pos: i32) {
+    unsafe { bridge::gmod_bridge_remove(state.ptr(), stack_pos) }
+}
+
+/// Pops the value at the top of the stack and replaces the value at `stack_pos` with it.
+/// Only works with stack positions, not with upvalues or special values.
+pub fn replace(state: LuaState, stack_pos: i32) {
+    let stack_pos = rel_to_abs(state, stack_pos);
+    if stack_pos == top(state) {
+        // replacing the top with itself just pops it
+        pop(state, 1);
+        return;
+    }
+    insert(state, stack_pos);
+    remove(state, stack_pos + 1);
+}
+
+/// Returns `true` if the values at `a` and `b` are equal, may call the `__eq` metamethod.
+pub fn equal(state: LuaState, a: i32, b: i32) -> bool {
+    unsafe { bridge::gmod_bridge_equal(state.ptr(), a, b) != 0 }
+}
+
+/// Returns `true` if the values at `a` and `b` are primitively equal, without calling metamethods.
+pub fn raw_equal(state: LuaState, a: i32, b: i32) -> bool {
+    unsafe { bridge::gmod_bridge_raw_equal(state.ptr(), a, b) != 0 }
+}
+
 /// Pushes `val` to the stack, returns the number of values pushed (usually just 1).
 pub fn push<T: ToStack>(state: LuaState, val: T) -> i32 {
     T::push(val, state)
 }
 
+/// Pushes `val` to the stack, fails if the stack cant grow to fit it. Check [ToStack::try_push].
+pub fn try_push<T: ToStack>(state: LuaState, val: T) -> Result<i32> {
+    T::try_push(val, state)
+}
+
 /// Pushes a copy of the value at `stack_pos` to the stack.
 pub fn push_copy(state: LuaState, stack_pos: i32) {
     unsafe { bridge::gmod_bridge_push(state.ptr(), stack_pos) }
@@ -238,21 +330,27 @@ pub fn get<T: FromStack>(state: LuaState, stack_pos: i32) -> Result<T> {
 
 /// Gets the string at `stack_pos`.
 pub fn get_string(state: LuaState, stack_pos: i32) -> Result<String, Utf8Error> {
-    let str_bytes = unsafe {
-        let mut outlen = 0;
-        let ptr = bridge::gmod_bridge_get_string(state.ptr(), stack_pos, &mut outlen) as *const u8;
-        std::slice::from_raw_parts(ptr, outlen as usize)
-    };
+    let str_bytes = unsafe { get_string_ref(state, stack_pos) };
     Ok(std::str::from_utf8(str_bytes)?.to_string())
 }
 
 /// Gets the string at `stack_pos` without trying to convert to valid utf-8.
 pub fn get_string_bytes(state: LuaState, stack_pos: i32) -> Vec<u8> {
-    unsafe {
-        let mut outlen = 0;
-        let ptr = bridge::gmod_bridge_get_string(state.ptr(), stack_pos, &mut outlen) as *const u8;
-        std::slice::from_raw_parts(ptr, outlen as usize).to_owned()
+    unsafe { get_string_ref(state, stack_pos) }.to_owned()
+}
+
+/// Borrows the bytes of the string at `stack_pos` without copying them.
+///
+/// # Safety
+/// The bytes are owned by lua, they are only valid while the string stays on the stack.
+/// Numbers are converted to strings in place, any other type returns an empty slice.
+pub unsafe fn get_string_ref<'a>(state: LuaState, stack_pos: i32) -> &'a [u8] {
+    let mut outlen = 0;
+    let ptr = bridge::gmod_bridge_get_string(state.ptr(), stack_pos, &mut outlen) as *const u8;
+    if ptr.is_null() {
+        return &[];
     }
+    std::slice::from_raw_parts(ptr, outlen as usize)
 }
 
 /// Gets the number at `stack_pos`.
@@ -267,6 +365,7 @@ pub fn get_bool(state: LuaState, stack_pos: i32) -> bool {
 
 /// Returns the vector at `stack_pos`.
 /// If the value at `stack_pos` is not a vector then it returns [0, 0, 0].
+/// Use `lua::get::<gmrs::Vector>` to get an error instead.
 pub fn get_vector(state: LuaState, stack_pos: i32) -> [f32; 3] {
     let mut vector = [0.0; 3];
     unsafe { bridge::gmod_bridge_get_vector(state.ptr(), stack_pos, vector.as_mut_ptr()) }
@@ -275,12 +374,27 @@ pub fn get_vector(state: LuaState, stack_pos: i32) -> [f32; 3] {
 
 /// Returns the angle at `stack_pos`.
 /// If the value at `stack_pos` is not a angle then it returns [0, 0, 0].
+/// Use `lua::get::<gmrs::Angle>` to get an error instead.
 pub fn get_angle(state: LuaState, stack_pos: i32) -> [f32; 3] {
     let mut angle = [0.0; 3];
     unsafe { bridge::gmod_bridge_get_angle(state.ptr(), stack_pos, angle.as_mut_ptr()) }
     angle
 }
 
+/// Returns the c function at `stack_pos` or `None` if the value is not a c function.
+pub fn get_c_function(state: LuaState, stack_pos: i32) -> Option<CFunc> {
+    unsafe { bridge::gmod_bridge_get_c_function(state.ptr(), stack_pos) }
+}
+
+/// Throws an error if the value at `stack_pos` doesnt have type `ty`. Check [expect_type]
+/// for a version that returns an error instead.
+///
+/// # Safety
+/// If the type doesnt match this function never returns, no destructors will be called.
+pub unsafe fn check_type(state: LuaState, stack_pos: i32, ty: LuaType) {
+    bridge::gmod_bridge_check_type(state.ptr(), stack_pos, ty.to_number())
+}
+
 /// Checks if the value at `stack_pos` has type `ty`.
 pub fn is_type(state: LuaState, stack_pos: i32, ty: LuaType) -> bool {
     unsafe { bridge::gmod_bridge_is_type(state.ptr(), stack_pos, ty.to_number()) }
@@ -330,21 +444,58 @@ pub fn set_table(state: LuaState, stack_pos: i32) {
     unsafe { bridge::gmod_bridge_set_table(state.ptr(), stack_pos) }
 }
 
+/// Same as [get_table] but doesnt call metamethods.
+pub fn raw_get(state: LuaState, stack_pos: i32) {
+    unsafe { bridge::gmod_bridge_raw_get(state.ptr(), stack_pos) }
+}
+
+/// Same as [set_table] but doesnt call metamethods.
+pub fn raw_set(state: LuaState, stack_pos: i32) {
+    unsafe { bridge::gmod_bridge_raw_set(state.ptr(), stack_pos) }
+}
+
+/// Pops a key from the stack and pushes the next key-value pair of the table at `stack_pos`.
+/// Returns `false` and pushes nothing when there are no more elements.
+/// To start the traversal push `nil` as the key.
+pub fn next(state: LuaState, stack_pos: i32) -> bool {
+    unsafe { bridge::gmod_bridge_next(state.ptr(), stack_pos) != 0 }
+}
+
 /// Pushes table\[key\] on to the stack  
 /// table = value at iStackPos  
 /// key   = strName  
-pub fn get_field(state: LuaState, stack_pos: i32, name: &str) {
-    let name = std::ffi::CString::new(name).unwrap();
-    unsafe { bridge::gmod_bridge_get_field(state.ptr(), stack_pos, name.as_ptr()) }
+///
+/// The return type depends on the name, see [FieldKey::Output]:
+/// - [FieldName], created with [field!](crate::field), and `&CStr` return `()`, they cant fail.
+/// - `&str` and `&String` return [Result]`<()>`, an error if the name contains a NUL byte. Nothing
+///   is pushed in that case.
+///
+///```
+///# use gmrs::prelude::*;
+/// fn push_print(state: LuaState) -> lua::Result<()> {
+///     lua::push_special(state, LuaSpecial::Glob);
+///     lua::get_field(state, -1, gmrs::field!("print"));
+///     let name = String::from("print");
+///     lua::get_field(state, -2, name.as_str())?;
+///     Ok(())
+/// }
+///```
+pub fn get_field<N: FieldKey>(state: LuaState, stack_pos: i32, name: N) -> N::Output {
+    name.with_c_str(|name| unsafe {
+        bridge::gmod_bridge_get_field(state.ptr(), stack_pos, name.as_ptr())
+    })
 }
 
 /// Sets table\[key\] to the value at the top of the stack.  
 /// table = value at iStackPos  
 /// key   = strName  
 /// Pops the value from the stack  
-pub fn set_field(state: LuaState, stack_pos: i32, name: &str) {
-    let name = std::ffi::CString::new(name).unwrap();
-    unsafe { bridge::gmod_bridge_set_field(state.ptr(), stack_pos, name.as_ptr()) }
+/// Names and return types are the same as in [get_field], [Result]`<()>` for `&str` and `()` for
+/// [FieldName]. If the name is invalid nothing is popped.
+pub fn set_field<N: FieldKey>(state: LuaState, stack_pos: i32, name: N) -> N::Output {
+    name.with_c_str(|name| unsafe {
+        bridge::gmod_bridge_set_field(state.ptr(), stack_pos, name.as_ptr())
+    })
 }
 
 /// To call a function first push the push function to stack then push
@@ -384,13 +535,20 @@ where
 
 /// Similar to [pcall] but if the function fails nothing is pushed to the stack and instead an error
 /// is returned. If the function succeds then Ok is returned and the results are pushed to the stack.
+/// String error objects become [Error::CustomBytes], any other value is kept as [Error::Value].
 pub fn pcall_result(state: LuaState, args: i32, results: i32) -> Result<()> {
     match pcall(state, args, results) {
         true => Ok(()),
         false => {
-            let err_msg = get_string_bytes(state, -1);
-            pop(state, 1);
-            Err(Error::CustomBytes(err_msg))
+            if is_type(state, -1, LuaType::String) {
+                let err_msg = get_string_bytes(state, -1);
+                pop(state, 1);
+                Err(Error::CustomBytes(err_msg))
+            } else {
+                let err = Error::from_stack_value(state, -1);
+                pop(state, 1);
+                Err(err)
+            }
         }
     }
 }
@@ -407,6 +565,59 @@ where
     pcall_result(state, arg_count, results)
 }
 
+/// Compiles `source` and pushes it as a function without running it.
+/// `chunk_name` is used in error messages and tracebacks, prefix it with `=` to show it as is,
+/// otherwise lua formats it like `[string "name"]`.
+/// Syntax errors are returned as [Error::Syntax] and nothing is pushed, [Error::MissingFunction]
+/// if `luaL_loadbuffer` couldnt be found in lua_shared.
+///
+///```
+///# use gmrs::prelude::*;
+/// fn compile_helper(state: LuaState) -> lua::Result<OwnedRef> {
+///     lua::load_buffer(state, "return ... * 2", "=helper")?;
+///     Ok(OwnedRef::from_top_of_stack(state))
+/// }
+///```
+pub fn load_buffer<S: AsRef<[u8]>>(state: LuaState, source: S, chunk_name: &str) -> Result<()> {
+    let source = source.as_ref();
+    let chunk_name = std::ffi::CString::new(chunk_name)?;
+    let status = unsafe {
+        bridge::gmod_bridge_load_buffer(
+            state.ptr(),
+            source.as_ptr() as *const std::os::raw::c_char,
+            source.len() as u32,
+            chunk_name.as_ptr(),
+        )
+    };
+    match status {
+        0 => Ok(()),
+        -1 => Err(Error::MissingFunction("luaL_loadbuffer")),
+        _ => {
+            let message = String::from_utf8_lossy(&get_string_bytes(state, -1)).into_owned();
+            pop(state, 1);
+            Err(Error::Syntax(message))
+        }
+    }
+}
+
+/// Compiles and runs `source`, returning everything the chunk returned.
+/// Syntax errors are returned as [Error::Syntax], errors while running like [pcall_result].
+///
+///```
+///# use gmrs::prelude::*;
+/// fn server_name(state: LuaState) -> lua::Result<String> {
+///     let results = lua::run_string(state, "return GetHostName()")?;
+///     results.get(state, 0)
+/// }
+///```
+pub fn run_string<S: AsRef<[u8]>>(state: LuaState, source: S) -> Result<MultiValue> {
+    let _guard = StackGuard::new(state);
+    let top = top(state);
+    load_buffer(state, source, "=RunString")?;
+    pcall_result(state, 0, MULT_RET)?;
+    get(state, top + 1)
+}
+
 /// Creates a new user data with size `size` and returns the pointer to the allocated memory.
 pub fn new_user_data(state: LuaState, size: u32) -> *mut std::ffi::c_void {
     unsafe { bridge::gmod_bridge_new_user_data(state.ptr(), size) }
@@ -433,15 +644,14 @@ pub fn get_metatable(state: LuaState, stack_pos: i32) -> bool {
 }
 
 /// # Safety
-/// Throws an error message. This function will never return.
-/// If you call this no destructors will be called and you risk leaking resources.
-pub unsafe fn throw_error(state: LuaState, error: String) -> ! {
-    let mut buffer = [0u8; 4096];
-    let copy_len = (buffer.len() - 1).min(error.as_bytes().len());
-    (&mut buffer[..copy_len]).copy_from_slice(&error.as_bytes()[..copy_len]);
-    buffer[copy_len] = 0;
-    drop(error);
-    bridge::gmod_bridge_throw_error(state.ptr(), buffer.as_ptr() as *const i8);
+/// Throws `error` by calling lua's `error` function with the error object. This function will never return.
+/// The error is moved to the lua stack before throwing but no other destructors will be called
+/// and you risk leaking resources.
+pub unsafe fn throw_error(state: LuaState, error: Error) -> ! {
+    push_special(state, LuaSpecial::Glob);
+    get_field(state, -1, crate::field!("error"));
+    push(state, error);
+    call(state, 1, 0);
     unreachable!()
 }
 
@@ -486,7 +696,7 @@ impl<R: ToStack + Send> ToStack for Closure<R> {
         }
         create_table(state);
         push(state, NativeFunc::new(closure_gc::<R>));
-        set_field(state, -2, "__gc");
+        set_field(state, -2, crate::field!("__gc"));
         set_metatable(state, -2);
         push_c_closure(state, closure_call::<R>, 1);
         1
@@ -515,14 +725,10 @@ where
 unsafe extern "C" fn closure_call<R: ToStack + Send>(state: LuaStateRaw) -> i32 {
     let state = LuaState::new(state);
     let ud = get_user_data(state, upvalue_index(1)) as *mut Closure<R>;
-    let result = (*ud).0(state);
+    let result = (*ud).0(state).and_then(|value| try_push(state, value));
     match result {
-        Ok(value) => push(state, value),
-        Err(e) => {
-            let msg = format!("{}", e);
-            drop(e);
-            throw_error(state, msg);
-        }
+        Ok(count) => count,
+        Err(e) => throw_error(state, e),
     }
 }
 
@@ -553,3 +759,19 @@ unsafe extern "C" fn closure_gc<R: ToStack + Send>(state: LuaStateRaw) -> i32 {
 pub fn error_message<S: Into<String>>(message: S) -> Result<()> {
     Err(error::Error::CustomMessage(message.into()))
 }
+
+/// Returns an error whose error object is `value`, lua error handlers will receive `value` as is.
+///
+///```
+///# use gmrs::prelude::*;
+/// #[gmrs::function]
+/// fn my_function(state: LuaState) -> lua::Result<()> {
+///     let err = lua::create_table(state);
+///     err.set(state, "code", 404);
+///     err.set(state, "message", "not found");
+///     lua::error_value(state, err)
+/// }
+///```
+pub fn error_value<T: ToStack>(state: LuaState, value: T) -> Result<()> {
+    Err(Error::value(state, value))
+}
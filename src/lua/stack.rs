@@ -1,12 +1,11 @@
 use super::{LuaState, LuaType, Result};
+use crate::refs::OwnedRef;
 use std::str::Utf8Error;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum FromStackError {
-    #[error(
-        "Invalid argument at position {stack_pos}, expected {expected_type:?} found {found_type:?}"
-    )]
+    #[error("{expected_type} expected, got {found_type}")]
     InvalidType {
         stack_pos: i32,
         expected_type: LuaType,
@@ -18,9 +17,17 @@ pub enum FromStackError {
     InvalidUserdataType,
 }
 
-pub trait ToStack {
+pub trait ToStack: Sized {
     /// returns how many values were pushed to the stack, usally just 1
     fn push(self, state: LuaState) -> i32;
+
+    /// Same as `push` but makes sure the stack has space for the value first.
+    /// Types that push many values, like collections, should override this and reserve
+    /// the space they need.
+    fn try_push(self, state: LuaState) -> Result<i32> {
+        super::reserve_stack(state, 1)?;
+        Ok(self.push(state))
+    }
 }
 
 pub trait FromStack: Sized {
@@ -28,6 +35,24 @@ pub trait FromStack: Sized {
     fn from_stack(state: LuaState, stack_pos: i32) -> Result<(Self, i32)>;
 }
 
+/// Like [FromStack] but the value can borrow from the stack, like `&str`, `&[u8]` and
+/// [LuaStr](super::LuaStr). Used by [gmrs::function] to get the arguments, they stay on the stack
+/// until the function returns, as long as the function doesnt remove, replace or pop them. Every
+/// [FromStack] type implements it.
+pub trait FromStackRef<'a>: Sized {
+    /// Returns the value and how many stack slots were used
+    ///
+    /// # Safety
+    /// The values used must stay on the stack for the lifetime `'a`.
+    unsafe fn from_stack_ref(state: LuaState, stack_pos: i32) -> Result<(Self, i32)>;
+}
+
+impl<'a, T: FromStack> FromStackRef<'a> for T {
+    unsafe fn from_stack_ref(state: LuaState, stack_pos: i32) -> Result<(Self, i32)> {
+        T::from_stack(state, stack_pos)
+    }
+}
+
 macro_rules! impl_number_stack_type {
     ($ty:ty) => {
         impl ToStack for $ty {
@@ -119,6 +144,12 @@ impl ToStack for () {
     }
 }
 
+impl FromStack for () {
+    fn from_stack(_state: LuaState, _stack_pos: i32) -> Result<(Self, i32)> {
+        Ok(((), 0))
+    }
+}
+
 impl FromStack for LuaState {
     fn from_stack(state: LuaState, _stack_pos: i32) -> Result<(Self, i32)> {
         Ok((state, 0))
@@ -135,6 +166,23 @@ impl<T: ToStack> ToStack for Option<T> {
             }
         }
     }
+
+    fn try_push(self, state: LuaState) -> Result<i32> {
+        match self {
+            Some(v) => v.try_push(state),
+            None => super::Nil.try_push(state),
+        }
+    }
+}
+
+/// `nil` and no value are `None`, anything else is converted to `T`.
+impl<T: FromStack> FromStack for Option<T> {
+    fn from_stack(state: LuaState, stack_pos: i32) -> Result<(Self, i32)> {
+        match super::get_type(state, stack_pos) {
+            LuaType::Nil | LuaType::None => Ok((None, 1)),
+            _ => T::from_stack(state, stack_pos).map(|(value, count)| (Some(value), count)),
+        }
+    }
 }
 
 impl ToStack for &[u8] {
@@ -143,3 +191,122 @@ impl ToStack for &[u8] {
         1
     }
 }
+
+macro_rules! impl_tuple_stack_type {
+    ($($name:ident),+) => {
+        /// Pushes every element, used to push multiple values like function arguments or results.
+        impl<$($name: ToStack),+> ToStack for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn push(self, state: LuaState) -> i32 {
+                let ($($name,)+) = self;
+                0 $(+ $name.push(state))+
+            }
+
+            #[allow(non_snake_case)]
+            fn try_push(self, state: LuaState) -> Result<i32> {
+                let ($($name,)+) = self;
+                Ok(0 $(+ $name.try_push(state)?)+)
+            }
+        }
+
+        /// Gets every element starting at `stack_pos`.
+        impl<$($name: FromStack),+> FromStack for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn from_stack(state: LuaState, stack_pos: i32) -> Result<(Self, i32)> {
+                let stack_pos = super::rel_to_abs(state, stack_pos);
+                let mut count = 0;
+                $(
+                    let ($name, used) = $name::from_stack(state, stack_pos + count)?;
+                    count += used;
+                )+
+                Ok((($($name,)+), count))
+            }
+        }
+    };
+}
+
+impl_tuple_stack_type!(A);
+impl_tuple_stack_type!(A, B);
+impl_tuple_stack_type!(A, B, C);
+impl_tuple_stack_type!(A, B, C, D);
+impl_tuple_stack_type!(A, B, C, D, E);
+impl_tuple_stack_type!(A, B, C, D, E, F);
+impl_tuple_stack_type!(A, B, C, D, E, F, G);
+impl_tuple_stack_type!(A, B, C, D, E, F, G, H);
+
+/// Any number of lua values, like the results of [super::run_string].
+/// When pushed every value is pushed, when taken from the stack it takes every value from the
+/// position to the top of the stack.
+#[derive(Debug, Default)]
+pub struct MultiValue(Vec<OwnedRef>);
+impl MultiValue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Converts the value at `index` to `T`, indexes start at 0.
+    /// Missing values are `nil` so `get::<Option<T>>` can be used for optional values.
+    pub fn get<T: FromStack>(&self, state: LuaState, index: usize) -> Result<T> {
+        let _guard = super::StackGuard::new(state);
+        super::reserve_stack(state, 1)?;
+        match self.0.get(index) {
+            Some(value) => value.push(state),
+            None => super::Nil.push(state),
+        };
+        super::get(state, -1)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, OwnedRef> {
+        self.0.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<OwnedRef> {
+        self.0
+    }
+}
+impl From<Vec<OwnedRef>> for MultiValue {
+    fn from(values: Vec<OwnedRef>) -> Self {
+        Self(values)
+    }
+}
+impl IntoIterator for MultiValue {
+    type Item = OwnedRef;
+    type IntoIter = std::vec::IntoIter<OwnedRef>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+impl ToStack for MultiValue {
+    fn push(self, state: LuaState) -> i32 {
+        let count = self.0.len() as i32;
+        for value in self.0 {
+            value.push(state);
+        }
+        count
+    }
+
+    fn try_push(self, state: LuaState) -> Result<i32> {
+        super::reserve_stack(state, self.0.len() as i32)?;
+        Ok(self.push(state))
+    }
+}
+impl FromStack for MultiValue {
+    fn from_stack(state: LuaState, stack_pos: i32) -> Result<(Self, i32)> {
+        let stack_pos = super::rel_to_abs(state, stack_pos);
+        let top = super::top(state);
+        let values: Vec<_> = (stack_pos..=top)
+            .map(|pos| OwnedRef::new(state, pos))
+            .collect();
+        let count = values.len() as i32;
+        Ok((Self(values), count))
+    }
+}
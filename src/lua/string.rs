@@ -0,0 +1,123 @@
+use super::{FromStackRef, LuaState, LuaType, Result, ToStack};
+use std::{borrow::Cow, str::Utf8Error};
+
+/// A lua string borrowed from the stack, the bytes are only checked to be valid utf-8 when
+/// converted with [LuaStr::to_str].
+///
+///```
+///# use gmrs::prelude::*;
+/// #[gmrs::function]
+/// fn count_lines(text: lua::LuaStr) -> lua::Result<usize> {
+///     Ok(text.as_bytes().iter().filter(|b| **b == b'\n').count())
+/// }
+///
+/// #[gmrs::function]
+/// fn starts_with(text: &str, prefix: &str) -> lua::Result<bool> {
+///     Ok(text.starts_with(prefix))
+/// }
+///```
+///
+/// The strings are only borrowed until the function returns, they cant be kept:
+///
+///```compile_fail
+///# use gmrs::prelude::*;
+/// #[gmrs::function]
+/// fn keep(text: &'static str) -> lua::Result<()> {
+///     std::thread::spawn(move || println!("{}", text));
+///     Ok(())
+/// }
+///```
+///
+///```compile_fail
+///# use gmrs::prelude::*;
+/// #[gmrs::function]
+/// fn keep(text: lua::LuaStr<'static>) -> lua::Result<()> {
+///     std::thread::spawn(move || println!("{:?}", text));
+///     Ok(())
+/// }
+///```
+///
+/// The arguments are what keeps the strings alive, while they are borrowed the function must not
+/// [remove](super::remove), [replace](super::replace) or [pop](super::pop) the argument slots
+/// at the bottom of the stack. Values pushed on top of them can be popped freely.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LuaStr<'a>(&'a [u8]);
+
+impl<'a> LuaStr<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Fails if the string is not valid utf-8.
+    pub fn to_str(&self) -> Result<&'a str, Utf8Error> {
+        std::str::from_utf8(self.0)
+    }
+
+    /// Replaces invalid utf-8 with `U+FFFD`, only allocates if the string is not valid utf-8.
+    pub fn to_string_lossy(&self) -> Cow<'a, str> {
+        String::from_utf8_lossy(self.0)
+    }
+}
+
+impl std::fmt::Debug for LuaStr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_string_lossy())
+    }
+}
+
+impl std::fmt::Display for LuaStr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_string_lossy())
+    }
+}
+
+impl<'a> From<&'a str> for LuaStr<'a> {
+    fn from(string: &'a str) -> Self {
+        Self(string.as_bytes())
+    }
+}
+
+impl<'a> From<&'a [u8]> for LuaStr<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl ToStack for LuaStr<'_> {
+    fn push(self, state: LuaState) -> i32 {
+        super::push_bytes(state, self.0);
+        1
+    }
+}
+
+impl<'a> FromStackRef<'a> for LuaStr<'a> {
+    unsafe fn from_stack_ref(state: LuaState, stack_pos: i32) -> Result<(Self, i32)> {
+        super::expect_type(state, stack_pos, LuaType::String)?;
+        Ok((Self(super::get_string_ref(state, stack_pos)), 1))
+    }
+}
+
+impl<'a> FromStackRef<'a> for &'a [u8] {
+    unsafe fn from_stack_ref(state: LuaState, stack_pos: i32) -> Result<(Self, i32)> {
+        LuaStr::from_stack_ref(state, stack_pos).map(|(string, count)| (string.as_bytes(), count))
+    }
+}
+
+impl<'a> FromStackRef<'a> for &'a str {
+    unsafe fn from_stack_ref(state: LuaState, stack_pos: i32) -> Result<(Self, i32)> {
+        let (string, count) = LuaStr::from_stack_ref(state, stack_pos)?;
+        Ok((string.to_str()?, count))
+    }
+}
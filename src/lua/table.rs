@@ -1,4 +1,8 @@
-use super::{FromStack, LuaState, LuaType, Result, ToStack};
+use super::{FromStack, LuaState, LuaType, Result, StackGuard, ToStack};
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
 
 pub enum TableKey<'a> {
     String(&'a str),
@@ -51,10 +55,13 @@ impl TableView {
     }
 
     /// Tries to get the value with `key`, fails if the conversion to `T` fails.
+    /// The value is popped after the conversion so `T` should not reference the stack,
+    /// use [TableView::push_value] to get a [TableView] of a nested table.
     pub fn get<'a, T>(&self, state: LuaState, key: impl Into<TableKey<'a>>) -> Result<T>
     where
         T: FromStack,
     {
+        let _guard = super::StackGuard::new(state);
         super::push(state, key.into());
         super::get_table(state, self.stack_pos());
         super::get(state, -1)
@@ -73,6 +80,27 @@ impl TableView {
         self.set(state, key, super::NIL)
     }
 
+    /// Same as [TableView::get] but doesnt call the `__index` metamethod.
+    pub fn raw_get<'a, T>(&self, state: LuaState, key: impl Into<TableKey<'a>>) -> Result<T>
+    where
+        T: FromStack,
+    {
+        let _guard = StackGuard::new(state);
+        super::push(state, key.into());
+        super::raw_get(state, self.stack_pos());
+        super::get(state, -1)
+    }
+
+    /// Same as [TableView::set] but doesnt call the `__newindex` metamethod.
+    pub fn raw_set<'a, T>(&self, state: LuaState, key: impl Into<TableKey<'a>>, value: T)
+    where
+        T: ToStack,
+    {
+        super::push(state, key.into());
+        super::push(state, value);
+        super::raw_set(state, self.stack_pos());
+    }
+
     /// Pushes the value with `key` on the stack.
     pub fn push_value<'a>(&self, state: LuaState, key: impl Into<TableKey<'a>>) {
         super::push(state, key.into());
@@ -91,3 +119,154 @@ impl FromStack for TableView {
         Ok((Self::new(state, stack_pos), 1))
     }
 }
+
+/// Pushes a new table and calls `fill` with it, if `fill` fails the table is popped.
+fn try_push_table<F>(state: LuaState, fill: F) -> Result<i32>
+where
+    F: FnOnce(TableView) -> Result<()>,
+{
+    // the table, a key and a value
+    super::reserve_stack(state, 3)?;
+    let guard = StackGuard::new(state);
+    let table = super::create_table(state);
+    fill(table)?;
+    guard.forget();
+    Ok(1)
+}
+
+/// Same as [try_push_table] for [ToStack::push], which cant report that the stack couldnt grow.
+fn push_table<F>(state: LuaState, fill: F) -> i32
+where
+    F: FnOnce(TableView),
+{
+    super::check_stack(state, 3);
+    let table = super::create_table(state);
+    fill(table);
+    1
+}
+
+/// Calls `insert` with every key-value pair of the table.
+fn for_each_pair<K, V, F>(state: LuaState, tbl: TableView, mut insert: F) -> Result<()>
+where
+    K: FromStack,
+    V: FromStack,
+    F: FnMut(K, V),
+{
+    super::reserve_stack(state, 3)?;
+    let _guard = StackGuard::new(state);
+    super::push_nil(state);
+    while super::next(state, tbl.stack_pos()) {
+        let key = super::get(state, -2)?;
+        let value = super::get(state, -1)?;
+        insert(key, value);
+        super::pop(state, 1);
+    }
+    Ok(())
+}
+
+/// A lua sequence, a table with elements from index 1 to n.
+/// `Vec` doesnt implement [ToStack] directly because `ToStack::push` would shadow `Vec::push`.
+/// Like maps, only [ToStack::try_push] reports an error if the stack cant grow.
+///
+///```
+///# use gmrs::prelude::*;
+/// #[gmrs::function]
+/// fn reversed(list: lua::Sequence<f64>) -> lua::Result<lua::Sequence<f64>> {
+///     let mut list = list.0;
+///     list.reverse();
+///     Ok(list.into())
+/// }
+///```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Sequence<T>(pub Vec<T>);
+
+impl<T> From<Vec<T>> for Sequence<T> {
+    fn from(list: Vec<T>) -> Self {
+        Self(list)
+    }
+}
+
+impl<T> From<Sequence<T>> for Vec<T> {
+    fn from(sequence: Sequence<T>) -> Self {
+        sequence.0
+    }
+}
+
+impl<T: ToStack> ToStack for Sequence<T> {
+    fn push(self, state: LuaState) -> i32 {
+        push_table(state, |table| {
+            for (index, value) in self.0.into_iter().enumerate() {
+                super::push(state, index + 1);
+                value.push(state);
+                super::set_table(state, table.stack_pos());
+            }
+        })
+    }
+
+    fn try_push(self, state: LuaState) -> Result<i32> {
+        try_push_table(state, |table| {
+            for (index, value) in self.0.into_iter().enumerate() {
+                super::push(state, index + 1);
+                value.try_push(state)?;
+                super::set_table(state, table.stack_pos());
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Reads elements starting at index 1 until the first `nil`.
+impl<T: FromStack> FromTable for Sequence<T> {
+    fn from_table(state: LuaState, tbl: TableView) -> Result<Self> {
+        super::reserve_stack(state, 2)?;
+        let mut list = Vec::new();
+        for index in 1.. {
+            let _guard = StackGuard::new(state);
+            tbl.push_value(state, index);
+            if super::is_type(state, -1, LuaType::Nil) {
+                break;
+            }
+            list.push(super::get(state, -1)?);
+        }
+        Ok(Self(list))
+    }
+}
+
+macro_rules! impl_map_stack_type {
+    ($map:ident, $($bound:path),*) => {
+        impl<K: ToStack, V: ToStack> ToStack for $map<K, V> {
+            fn push(self, state: LuaState) -> i32 {
+                push_table(state, |table| {
+                    for (key, value) in self {
+                        key.push(state);
+                        value.push(state);
+                        super::set_table(state, table.stack_pos());
+                    }
+                })
+            }
+
+            fn try_push(self, state: LuaState) -> Result<i32> {
+                try_push_table(state, |table| {
+                    for (key, value) in self {
+                        key.try_push(state)?;
+                        value.try_push(state)?;
+                        super::set_table(state, table.stack_pos());
+                    }
+                    Ok(())
+                })
+            }
+        }
+        impl<K: FromStack $(+ $bound)*, V: FromStack> FromTable for $map<K, V> {
+            fn from_table(state: LuaState, tbl: TableView) -> Result<Self> {
+                let mut map = $map::new();
+                for_each_pair(state, tbl, |key, value| {
+                    map.insert(key, value);
+                })?;
+                Ok(map)
+            }
+        }
+    };
+}
+
+impl_map_stack_type!(HashMap, Eq, Hash);
+impl_map_stack_type!(BTreeMap, Ord);
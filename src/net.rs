@@ -0,0 +1,643 @@
+//! Wraps gmod's `net` library, used to send messages between the server and the clients.
+//!
+//! Messages can be written field by field with a [NetWriter] or all at once by deriving
+//! [NetMessage]. Integer fields are sent with their full size unless `#[net(bits = N)]` is used.
+//!
+//!```
+//! use gmrs::prelude::*;
+//! use gmrs::net::{self, NetMessage};
+//!
+//! #[derive(Debug, NetMessage)]
+//! #[net(name = "my_module_hit")]
+//! struct Hit {
+//!     target: Entity,
+//!     #[net(bits = 10)]
+//!     damage: u32,
+//!     pos: Vector,
+//!     weapon: Option<String>,
+//! }
+//!
+//! fn setup(state: LuaState) -> lua::Result<()> {
+//!     net::add_network_string(state, Hit::NAME)?;
+//!     net::receive_message(state, |state, hit: Hit, ply| {
+//!         gmrs::print!(state, "{:?} sent by {:?}", hit, ply);
+//!         Ok(())
+//!     })
+//! }
+//!
+//! fn send_hit(state: LuaState, ply: &Player, hit: &Hit) -> lua::Result<()> {
+//!     hit.start(state)?.send(ply)
+//! }
+//!```
+use crate::{
+    internal::{self, StateId},
+    lua::{self, FieldName, FromStack, LuaSpecial, LuaState, StackGuard, ToStack},
+    Angle, Color, Entity, Player, Vector,
+};
+use std::{collections::HashSet, sync::Mutex};
+
+pub use gmrs_impl::NetMessage;
+
+lazy_static! {
+    /// Names of the receivers added with [receive] that havent been removed yet, by state.
+    static ref REGISTERED_RECEIVERS: Mutex<HashSet<(StateId, String)>> = Mutex::new(HashSet::new());
+}
+
+/// A struct that can be sent as a single net message, usually derived.
+///
+/// The derive accepts `#[net(name = "...")]` on the struct to set [NetMessage::NAME], it defaults
+/// to the name of the struct. Fields accept `#[net(bits = N)]` for integers and `#[net(skip)]`
+/// for fields that are not sent and use [Default] when read.
+pub trait NetMessage: Sized {
+    /// The name of the message, must be registered with [add_network_string] on the server.
+    const NAME: &'static str;
+
+    fn write(&self, writer: &mut NetWriter) -> lua::Result<()>;
+    fn read(reader: &mut NetReader) -> lua::Result<Self>;
+
+    /// Starts the message and writes it, use one of the send functions of [NetWriter] to send it.
+    fn start(&self, state: LuaState) -> lua::Result<NetWriter> {
+        let mut writer = start(state, Self::NAME, false)?;
+        self.write(&mut writer)?;
+        Ok(writer)
+    }
+}
+
+/// A value that can be written to and read from a net message.
+pub trait NetValue: Sized {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()>;
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self>;
+}
+
+/// An integer that can be sent with a custom number of bits, used by `#[net(bits = N)]`.
+pub trait NetBits: Sized {
+    fn net_write_bits(&self, writer: &mut NetWriter, bits: u32) -> lua::Result<()>;
+    fn net_read_bits(reader: &mut NetReader, bits: u32) -> lua::Result<Self>;
+}
+
+/// A message that is being written, created by [start].
+#[derive(Debug)]
+#[must_use = "the message is only sent by one of the send functions"]
+pub struct NetWriter {
+    state: LuaState,
+}
+
+impl NetWriter {
+    pub fn state(&self) -> LuaState {
+        self.state
+    }
+
+    /// Equivalent to `net.WriteUInt(value, bits)`, `bits` must be between 1 and 32.
+    pub fn write_uint(&mut self, value: u32, bits: u32) -> lua::Result<()> {
+        check_bits(bits)?;
+        self.call(crate::field!("WriteUInt"), |state| {
+            lua::push(state, value);
+            lua::push(state, bits);
+        })
+    }
+
+    /// Equivalent to `net.WriteInt(value, bits)`, `bits` must be between 1 and 32.
+    pub fn write_int(&mut self, value: i32, bits: u32) -> lua::Result<()> {
+        check_bits(bits)?;
+        self.call(crate::field!("WriteInt"), |state| {
+            lua::push(state, value);
+            lua::push(state, bits);
+        })
+    }
+
+    pub fn write_bool(&mut self, value: bool) -> lua::Result<()> {
+        self.call(crate::field!("WriteBool"), |state| {
+            lua::push(state, value);
+        })
+    }
+
+    pub fn write_float(&mut self, value: f32) -> lua::Result<()> {
+        self.call(crate::field!("WriteFloat"), |state| {
+            lua::push(state, value);
+        })
+    }
+
+    pub fn write_double(&mut self, value: f64) -> lua::Result<()> {
+        self.call(crate::field!("WriteDouble"), |state| {
+            lua::push(state, value);
+        })
+    }
+
+    /// Equivalent to `net.WriteString(value)`, the string is cut at the first null byte.
+    /// Use [NetWriter::write_data] for binary data.
+    pub fn write_string(&mut self, value: &str) -> lua::Result<()> {
+        self.call(crate::field!("WriteString"), |state| {
+            lua::push(state, value);
+        })
+    }
+
+    /// Equivalent to `net.WriteData(data, #data)`, the length is not written.
+    pub fn write_data(&mut self, data: &[u8]) -> lua::Result<()> {
+        self.call(crate::field!("WriteData"), |state| {
+            lua::push(state, data);
+            lua::push(state, data.len());
+        })
+    }
+
+    pub fn write_vector(&mut self, value: Vector) -> lua::Result<()> {
+        self.call(crate::field!("WriteVector"), |state| {
+            lua::push(state, value);
+        })
+    }
+
+    pub fn write_angle(&mut self, value: Angle) -> lua::Result<()> {
+        self.call(crate::field!("WriteAngle"), |state| {
+            lua::push(state, value);
+        })
+    }
+
+    /// Equivalent to `net.WriteColor(value, true)`.
+    pub fn write_color(&mut self, value: Color) -> lua::Result<()> {
+        self.call(crate::field!("WriteColor"), |state| {
+            lua::push(state, value);
+            lua::push(state, true);
+        })
+    }
+
+    pub fn write_entity(&mut self, value: &Entity) -> lua::Result<()> {
+        self.call(crate::field!("WriteEntity"), |state| {
+            lua::push(state, value);
+        })
+    }
+
+    /// Writes any [NetValue].
+    pub fn write<T: NetValue>(&mut self, value: &T) -> lua::Result<()> {
+        value.net_write(self)
+    }
+
+    /// Equivalent to `net.BytesWritten()`.
+    pub fn bytes_written(&self) -> lua::Result<u32> {
+        let _guard = StackGuard::new(self.state);
+        call_net(self.state, crate::field!("BytesWritten"), 1, |_| {})?;
+        lua::get(self.state, -1)
+    }
+
+    /// Equivalent to `net.Send(target)`, `target` can be a player or a [lua::Sequence] of players.
+    /// Only available on the server.
+    pub fn send<T: ToStack>(self, target: T) -> lua::Result<()> {
+        let state = self.state;
+        let _guard = StackGuard::new(state);
+        call_net(state, crate::field!("Send"), 0, |state| {
+            lua::push(state, target);
+        })
+    }
+
+    /// Equivalent to `net.SendOmit(target)`, sends to every player except `target`.
+    /// Only available on the server.
+    pub fn send_omit<T: ToStack>(self, target: T) -> lua::Result<()> {
+        let state = self.state;
+        let _guard = StackGuard::new(state);
+        call_net(state, crate::field!("SendOmit"), 0, |state| {
+            lua::push(state, target);
+        })
+    }
+
+    /// Equivalent to `net.SendPVS(pos)`, sends to the players that can potentially see `pos`.
+    /// Only available on the server.
+    pub fn send_pvs(self, pos: Vector) -> lua::Result<()> {
+        let state = self.state;
+        let _guard = StackGuard::new(state);
+        call_net(state, crate::field!("SendPVS"), 0, |state| {
+            lua::push(state, pos);
+        })
+    }
+
+    /// Equivalent to `net.Broadcast()`. Only available on the server.
+    pub fn broadcast(self) -> lua::Result<()> {
+        let state = self.state;
+        let _guard = StackGuard::new(state);
+        call_net(state, crate::field!("Broadcast"), 0, |_| {})
+    }
+
+    /// Equivalent to `net.SendToServer()`. Only available on the client.
+    pub fn send_to_server(self) -> lua::Result<()> {
+        let state = self.state;
+        let _guard = StackGuard::new(state);
+        call_net(state, crate::field!("SendToServer"), 0, |_| {})
+    }
+
+    fn call<F: FnOnce(LuaState)>(&mut self, name: FieldName, args: F) -> lua::Result<()> {
+        let _guard = StackGuard::new(self.state);
+        call_net(self.state, name, 0, args)
+    }
+}
+
+/// The message being received, only available inside the callback given to [receive].
+#[derive(Debug)]
+pub struct NetReader {
+    state: LuaState,
+    length: u32,
+}
+
+impl NetReader {
+    pub fn state(&self) -> LuaState {
+        self.state
+    }
+
+    /// The length of the message in bits.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Equivalent to `net.ReadUInt(bits)`, `bits` must be between 1 and 32.
+    pub fn read_uint(&mut self, bits: u32) -> lua::Result<u32> {
+        check_bits(bits)?;
+        self.call(crate::field!("ReadUInt"), |state| {
+            lua::push(state, bits);
+        })
+    }
+
+    /// Equivalent to `net.ReadInt(bits)`, `bits` must be between 1 and 32.
+    pub fn read_int(&mut self, bits: u32) -> lua::Result<i32> {
+        check_bits(bits)?;
+        self.call(crate::field!("ReadInt"), |state| {
+            lua::push(state, bits);
+        })
+    }
+
+    pub fn read_bool(&mut self) -> lua::Result<bool> {
+        self.call(crate::field!("ReadBool"), |_| {})
+    }
+
+    pub fn read_float(&mut self) -> lua::Result<f32> {
+        self.call(crate::field!("ReadFloat"), |_| {})
+    }
+
+    pub fn read_double(&mut self) -> lua::Result<f64> {
+        self.call(crate::field!("ReadDouble"), |_| {})
+    }
+
+    pub fn read_string(&mut self) -> lua::Result<String> {
+        self.call(crate::field!("ReadString"), |_| {})
+    }
+
+    /// Equivalent to `net.ReadData(length)`.
+    pub fn read_data(&mut self, length: usize) -> lua::Result<Vec<u8>> {
+        if length == 0 {
+            return Ok(Vec::new());
+        }
+        self.call(crate::field!("ReadData"), |state| {
+            lua::push(state, length);
+        })
+    }
+
+    pub fn read_vector(&mut self) -> lua::Result<Vector> {
+        self.call(crate::field!("ReadVector"), |_| {})
+    }
+
+    pub fn read_angle(&mut self) -> lua::Result<Angle> {
+        self.call(crate::field!("ReadAngle"), |_| {})
+    }
+
+    /// Equivalent to `net.ReadColor(true)`.
+    pub fn read_color(&mut self) -> lua::Result<Color> {
+        self.call(crate::field!("ReadColor"), |state| {
+            lua::push(state, true);
+        })
+    }
+
+    /// Equivalent to `net.ReadEntity()`, returns `None` if the entity is NULL, for example
+    /// when it doesnt exist on the client.
+    pub fn read_entity(&mut self) -> lua::Result<Option<Entity>> {
+        let state = self.state;
+        let _guard = StackGuard::new(state);
+        call_net(state, crate::field!("ReadEntity"), 1, |_| {})?;
+        Ok(lua::get::<Entity>(state, -1).ok())
+    }
+
+    /// Reads any [NetValue].
+    pub fn read<T: NetValue>(&mut self) -> lua::Result<T> {
+        T::net_read(self)
+    }
+
+    /// Equivalent to `net.BytesLeft()`.
+    pub fn bytes_left(&self) -> lua::Result<u32> {
+        let _guard = StackGuard::new(self.state);
+        call_net(self.state, crate::field!("BytesLeft"), 1, |_| {})?;
+        lua::get(self.state, -1)
+    }
+
+    fn call<T, F>(&mut self, name: FieldName, args: F) -> lua::Result<T>
+    where
+        T: FromStack,
+        F: FnOnce(LuaState),
+    {
+        let _guard = StackGuard::new(self.state);
+        call_net(self.state, name, 1, args)?;
+        lua::get(self.state, -1)
+    }
+}
+
+/// Equivalent to `util.AddNetworkString(name)`, every message must be registered on the server
+/// before it can be sent.
+pub fn add_network_string(state: LuaState, name: &str) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
+    lua::push_special(state, LuaSpecial::Glob);
+    lua::get_field(state, -1, crate::field!("util"));
+    lua::get_field(state, -1, crate::field!("AddNetworkString"));
+    lua::pcall_result_with(state, 0, |state| {
+        lua::push(state, name);
+    })
+}
+
+/// Equivalent to `net.Start(name, unreliable)`.
+pub fn start(state: LuaState, name: &str, unreliable: bool) -> lua::Result<NetWriter> {
+    let _guard = StackGuard::new(state);
+    call_net(state, crate::field!("Start"), 0, |state| {
+        lua::push(state, name);
+        lua::push(state, unreliable);
+    })?;
+    Ok(NetWriter { state })
+}
+
+/// Equivalent to `net.Receive(name, handler)`.
+/// `handler` is called with the message and the player that sent it, the player is `None` when
+/// the message was sent by the server. The receiver is removed automatically when the module is
+/// closed.
+pub fn receive<F>(state: LuaState, name: &str, mut handler: F) -> lua::Result<()>
+where
+    F: FnMut(LuaState, &mut NetReader, Option<Player>) -> lua::Result<()> + Send + 'static,
+{
+    let _guard = StackGuard::new(state);
+    call_net(state, crate::field!("Receive"), 0, |state| {
+        lua::push(state, name);
+        lua::push_closure(state, move |state| {
+            // receive callback : function(len, ply)
+            let mut reader = NetReader {
+                state,
+                length: lua::get(state, 1)?,
+            };
+            let player: Option<Player> = lua::get(state, 2).ok().flatten();
+            handler(state, &mut reader, player)
+        });
+    })?;
+    REGISTERED_RECEIVERS
+        .lock()
+        .unwrap()
+        .insert((internal::state_id(state), name.to_lowercase()));
+    Ok(())
+}
+
+/// Same as [receive] but the message is read as `M`.
+pub fn receive_message<M, F>(state: LuaState, mut handler: F) -> lua::Result<()>
+where
+    M: NetMessage,
+    F: FnMut(LuaState, M, Option<Player>) -> lua::Result<()> + Send + 'static,
+{
+    receive(state, M::NAME, move |state, reader, player| {
+        let message = M::read(reader)?;
+        handler(state, message, player)
+    })
+}
+
+/// Removes the receiver of `name`, equivalent to `net.Receivers[name:lower()] = nil`.
+pub fn remove_receiver(state: LuaState, name: &str) {
+    let name = name.to_lowercase();
+    let key = (internal::state_id(state), name);
+    REGISTERED_RECEIVERS.lock().unwrap().remove(&key);
+    remove_receiver_raw(state, &key.1);
+}
+
+/// Removes every receiver added with [receive] in `state`, called automatically by [gmrs::exit].
+pub fn remove_all(state: LuaState) {
+    let current = internal::state_id(state);
+    let mut receivers = Vec::new();
+    REGISTERED_RECEIVERS
+        .lock()
+        .unwrap()
+        .retain(|(owner, name)| {
+            if *owner == current {
+                receivers.push(name.clone());
+            }
+            *owner != current
+        });
+    for name in receivers {
+        remove_receiver_raw(state, &name);
+    }
+}
+
+fn remove_receiver_raw(state: LuaState, name: &str) {
+    let _guard = StackGuard::new(state);
+    lua::push_special(state, LuaSpecial::Glob);
+    lua::get_field(state, -1, crate::field!("net"));
+    if !lua::is_type(state, -1, lua::LuaType::Table) {
+        return;
+    }
+    lua::get_field(state, -1, crate::field!("Receivers"));
+    if lua::is_type(state, -1, lua::LuaType::Table) {
+        lua::push_nil(state);
+        // the receiver couldnt have been added if the name had a NUL byte
+        let _ = lua::set_field(state, -2, name);
+    }
+}
+
+fn check_bits(bits: u32) -> lua::Result<()> {
+    if (1..=32).contains(&bits) {
+        Ok(())
+    } else {
+        Err(lua::Error::CustomMessage(format!(
+            "invalid number of bits {}, must be between 1 and 32",
+            bits
+        )))
+    }
+}
+
+/// Calls `net[name]`, the results are left on the stack.
+fn call_net<F: FnOnce(LuaState)>(
+    state: LuaState,
+    name: FieldName,
+    results: i32,
+    args: F,
+) -> lua::Result<()> {
+    lua::push_special(state, LuaSpecial::Glob);
+    lua::get_field(state, -1, crate::field!("net"));
+    lua::get_field(state, -1, name);
+    lua::remove(state, -2);
+    lua::remove(state, -2);
+    lua::pcall_result_with(state, results, args)
+}
+
+macro_rules! impl_uint_net_value {
+    ($ty:ty, $bits:expr) => {
+        impl NetValue for $ty {
+            fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+                writer.write_uint(*self as u32, $bits)
+            }
+
+            fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+                Ok(reader.read_uint($bits)? as $ty)
+            }
+        }
+
+        impl NetBits for $ty {
+            fn net_write_bits(&self, writer: &mut NetWriter, bits: u32) -> lua::Result<()> {
+                writer.write_uint(*self as u32, bits)
+            }
+
+            fn net_read_bits(reader: &mut NetReader, bits: u32) -> lua::Result<Self> {
+                Ok(reader.read_uint(bits)? as $ty)
+            }
+        }
+    };
+}
+
+macro_rules! impl_int_net_value {
+    ($ty:ty, $bits:expr) => {
+        impl NetValue for $ty {
+            fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+                writer.write_int(*self as i32, $bits)
+            }
+
+            fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+                Ok(reader.read_int($bits)? as $ty)
+            }
+        }
+
+        impl NetBits for $ty {
+            fn net_write_bits(&self, writer: &mut NetWriter, bits: u32) -> lua::Result<()> {
+                writer.write_int(*self as i32, bits)
+            }
+
+            fn net_read_bits(reader: &mut NetReader, bits: u32) -> lua::Result<Self> {
+                Ok(reader.read_int(bits)? as $ty)
+            }
+        }
+    };
+}
+
+impl_uint_net_value!(u8, 8);
+impl_uint_net_value!(u16, 16);
+impl_uint_net_value!(u32, 32);
+impl_int_net_value!(i8, 8);
+impl_int_net_value!(i16, 16);
+impl_int_net_value!(i32, 32);
+
+/// Written as two 32 bit integers, the low bits first.
+impl NetValue for u64 {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+        writer.write_uint(*self as u32, 32)?;
+        writer.write_uint((*self >> 32) as u32, 32)
+    }
+
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+        let low = reader.read_uint(32)? as u64;
+        let high = reader.read_uint(32)? as u64;
+        Ok(low | (high << 32))
+    }
+}
+
+impl NetValue for i64 {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+        (*self as u64).net_write(writer)
+    }
+
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+        Ok(u64::net_read(reader)? as i64)
+    }
+}
+
+macro_rules! impl_net_value {
+    ($ty:ty, $write:ident, $read:ident) => {
+        impl NetValue for $ty {
+            fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+                writer.$write(*self)
+            }
+
+            fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+                reader.$read()
+            }
+        }
+    };
+}
+
+impl_net_value!(bool, write_bool, read_bool);
+impl_net_value!(f32, write_float, read_float);
+impl_net_value!(f64, write_double, read_double);
+impl_net_value!(Vector, write_vector, read_vector);
+impl_net_value!(Angle, write_angle, read_angle);
+impl_net_value!(Color, write_color, read_color);
+
+impl NetValue for String {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+        writer.write_string(self)
+    }
+
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+        reader.read_string()
+    }
+}
+
+impl NetValue for Entity {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+        writer.write_entity(self)
+    }
+
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+        reader
+            .read_entity()?
+            .ok_or_else(|| lua::Error::CustomMessage("NULL entity".to_string()))
+    }
+}
+
+impl NetValue for Player {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+        writer.write_entity(self)
+    }
+
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+        let state = reader.state;
+        Entity::net_read(reader)?
+            .into_player(state)
+            .ok_or_else(|| lua::Error::CustomMessage("Player expected, got Entity".to_string()))
+    }
+}
+
+/// Written as a bool followed by the value if there is one.
+impl<T: NetValue> NetValue for Option<T> {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+        writer.write_bool(self.is_some())?;
+        match self {
+            Some(value) => value.net_write(writer),
+            None => Ok(()),
+        }
+    }
+
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+        match reader.read_bool()? {
+            true => Ok(Some(T::net_read(reader)?)),
+            false => Ok(None),
+        }
+    }
+}
+
+/// Written as the number of elements, a 32 bit integer, followed by the elements.
+impl<T: NetValue> NetValue for Vec<T> {
+    fn net_write(&self, writer: &mut NetWriter) -> lua::Result<()> {
+        writer.write_uint(self.len() as u32, 32)?;
+        for value in self {
+            value.net_write(writer)?;
+        }
+        Ok(())
+    }
+
+    fn net_read(reader: &mut NetReader) -> lua::Result<Self> {
+        let length = reader.read_uint(32)?;
+        // every element takes at least one bit, dont trust the length sent by clients
+        if length as u64 > reader.bytes_left()? as u64 * 8 {
+            return Err(lua::Error::CustomMessage(format!(
+                "invalid sequence length {}",
+                length
+            )));
+        }
+        let mut list = Vec::new();
+        for _ in 0..length {
+            list.push(T::net_read(reader)?);
+        }
+        Ok(list)
+    }
+}
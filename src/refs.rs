@@ -1,17 +1,17 @@
 use crate::{
-    internal,
+    internal::{self, StateId},
     lua::{self, FromStack, LuaState, ToStack},
 };
-use std::sync::{
-    atomic::{AtomicI32, Ordering},
-    Arc,
-};
+use crossbeam::atomic::AtomicCell;
+use std::sync::Arc;
 
 // https://www.lua.org/source/5.1/lauxlib.h.html#LUA_NOREF
 const LUA_NOREF: i32 = -2;
 
+/// A reference in the registry of the state it was created in.
+/// When dropped outside of that state the reference is freed by its state the next `Think`.
 #[derive(Debug)]
-pub struct OwnedRef(i32);
+pub struct OwnedRef(RawRef);
 impl OwnedRef {
     /// Creates a new [OwnedRef] of the element at `stack_pos`
     pub fn new(state: LuaState, stack_pos: i32) -> Self {
@@ -21,14 +21,18 @@ impl OwnedRef {
 
     /// Creates a new [OwnedRef] of the element at the top of the stack and pops it off
     pub fn from_top_of_stack(state: LuaState) -> Self {
-        let reference = lua::reference_create(state);
-        Self(reference)
+        Self(RawRef::from_top_of_stack(state))
+    }
+
+    /// The state the reference belongs to.
+    pub fn state(&self) -> StateId {
+        self.0.state
     }
 }
 
 impl Drop for OwnedRef {
     fn drop(&mut self) {
-        drop_reference(self.0)
+        self.0.free()
     }
 }
 
@@ -46,8 +50,7 @@ impl ToStack for OwnedRef {
 
 impl ToStack for &OwnedRef {
     fn push(self, state: LuaState) -> i32 {
-        lua::reference_push(state, self.0);
-        1
+        self.0.push(state)
     }
 }
 
@@ -61,6 +64,11 @@ impl ArcRef {
     pub fn from_top_of_stack(state: LuaState) -> Self {
         Self(Arc::new(OwnedRef::from_top_of_stack(state)))
     }
+
+    /// The state the reference belongs to, see [OwnedRef::state].
+    pub fn state(&self) -> StateId {
+        self.0.state()
+    }
 }
 
 impl From<OwnedRef> for ArcRef {
@@ -92,18 +100,18 @@ impl AtomicRef {
 
     /// Creates a new [AtomicRef] of the element at the top of the stack and pops it off
     pub fn from_top_of_stack(state: LuaState) -> Self {
-        let reference = lua::reference_create(state);
-        Self(Arc::new(AtomicInternal(AtomicI32::new(reference))))
+        Self(Arc::new(AtomicInternal(AtomicCell::new(
+            RawRef::from_top_of_stack(state),
+        ))))
     }
 
     pub fn nill() -> Self {
-        Self(Arc::new(AtomicInternal(AtomicI32::new(LUA_NOREF))))
+        Self(Arc::new(AtomicInternal(AtomicCell::new(RawRef::NONE))))
     }
 
     pub fn replace(&self, mut reference: OwnedRef) {
-        let new_ref = reference.0;
-        reference.0 = self.0.load();
-        self.0.store(new_ref);
+        // the previous reference is freed when `reference` is dropped
+        reference.0 = self.0 .0.swap(reference.0);
     }
 }
 
@@ -117,7 +125,7 @@ impl From<OwnedRef> for AtomicRef {
     fn from(owned_ref: OwnedRef) -> Self {
         let reference = owned_ref.0;
         std::mem::forget(owned_ref);
-        Self(Arc::new(AtomicInternal(AtomicI32::new(reference))))
+        Self(Arc::new(AtomicInternal(AtomicCell::new(reference))))
     }
 }
 
@@ -129,34 +137,62 @@ impl ToStack for AtomicRef {
 
 impl ToStack for &AtomicRef {
     fn push(self, state: LuaState) -> i32 {
-        lua::reference_push(state, self.0.load());
-        1
+        self.0 .0.load().push(state)
     }
 }
 
 #[derive(Debug)]
-struct AtomicInternal(AtomicI32);
-impl AtomicInternal {
-    fn store(&self, reference: i32) {
-        self.0.store(reference, Ordering::Relaxed)
-    }
-
-    fn load(&self) -> i32 {
-        self.0.load(Ordering::Relaxed)
-    }
-}
+struct AtomicInternal(AtomicCell<RawRef>);
 
 impl Drop for AtomicInternal {
     fn drop(&mut self) {
-        let reference = self.0.load(Ordering::Relaxed);
-        drop_reference(reference)
+        self.0.load().free()
     }
 }
 
-fn drop_reference(reference: i32) {
-    if let Some(state) = crate::internal::get_lua_state() {
-        lua::reference_free(state, reference);
-    } else {
-        internal::remote_reference_free(reference);
+/// A registry reference and the state that owns it.
+#[derive(Debug, Clone, Copy)]
+struct RawRef {
+    reference: i32,
+    state: StateId,
+}
+
+impl RawRef {
+    const NONE: Self = Self {
+        reference: LUA_NOREF,
+        state: StateId::NONE,
+    };
+
+    fn from_top_of_stack(state: LuaState) -> Self {
+        Self {
+            state: internal::state_id(state),
+            reference: lua::reference_create(state),
+        }
+    }
+
+    fn push(self, state: LuaState) -> i32 {
+        debug_assert!(
+            self.reference == LUA_NOREF || internal::state_id(state) == self.state,
+            "pushed a reference of {:?} into {:?}",
+            self.state,
+            internal::state_id(state)
+        );
+        lua::reference_push(state, self.reference);
+        1
+    }
+
+    /// Frees the reference now if it belongs to the current state, otherwise queues it.
+    /// The reference is leaked if its state doesnt have the module open anymore.
+    fn free(self) {
+        if self.reference == LUA_NOREF {
+            return;
+        }
+        if internal::current_state_id() == Some(self.state) {
+            if let Some(state) = internal::get_lua_state() {
+                lua::reference_free(state, self.reference);
+                return;
+            }
+        }
+        internal::remote_reference_free(self.state, self.reference);
     }
 }
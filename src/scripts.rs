@@ -0,0 +1,100 @@
+//! Lua scripts embedded in the module binary.
+//!
+//! [include_lua!](crate::include_lua) embeds a file at compile time, relative to the file calling
+//! it like `include_str!`. The scripts given to [gmrs::entry] run in order after the entry function
+//! returns, so the native functions it registered are available, and receive the module table
+//! as `...`.
+//!
+//!```
+//! use gmrs::prelude::*;
+//!
+//! #[gmrs::function]
+//! fn add(a: f64, b: f64) -> lua::Result<f64> {
+//!     Ok(a + b)
+//! }
+//!
+//! fn register(state: LuaState) -> lua::Result<()> {
+//!     let module = lua::create_table(state);
+//!     module.set(state, "add", NativeFunc::new(add));
+//!     let module = OwnedRef::from_top_of_stack(state);
+//!     let script = gmrs::scripts::LuaScript::new(
+//!         "sum.lua",
+//!         "local module = ... function module.sum(...) local total = 0 for _, v in ipairs({...}) do total = module.add(total, v) end return total end",
+//!     );
+//!     script.run(state, &module)?;
+//!     Ok(())
+//! }
+//!```
+use crate::lua::{self, LuaState, MultiValue, ResultExt, StackGuard, ToStack};
+
+/// Embeds a lua file as a [LuaScript], the path is relative to the file calling the macro.
+#[macro_export]
+macro_rules! include_lua {
+    ($path:literal) => {
+        $crate::scripts::LuaScript::new($path, include_str!($path))
+    };
+}
+
+/// The source of a lua script and the name shown in its errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LuaScript {
+    name: &'static str,
+    source: &'static str,
+}
+
+impl LuaScript {
+    pub const fn new(name: &'static str, source: &'static str) -> Self {
+        Self { name, source }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /// Runs the script with `args` as `...` and returns everything it returned.
+    pub fn run<A: ToStack>(&self, state: LuaState, args: A) -> lua::Result<MultiValue> {
+        let _guard = StackGuard::new(state);
+        let top = lua::top(state);
+        lua::load_buffer(state, self.source, &format!("@{}", self.name))?;
+        lua::pcall_result_with(state, lua::MULT_RET, |state| {
+            lua::push(state, args);
+        })?;
+        lua::get(state, top + 1)
+    }
+}
+
+/// Runs `scripts` in order with the global table `module` as `...`, the table is created if it
+/// doesnt exist. Without a module `...` is nil.
+/// Stops at the first script that fails. Used by [gmrs::entry].
+pub fn run_all(state: LuaState, module: Option<&str>, scripts: &[LuaScript]) -> lua::Result<()> {
+    let _guard = StackGuard::new(state);
+    let module = match module {
+        Some(name) => Some(module_table(state, name)?),
+        None => None,
+    };
+    for script in scripts {
+        script
+            .run(state, module.as_ref())
+            .with_context(|| format!("failed to run '{}'", script.name))?;
+    }
+    Ok(())
+}
+
+/// Returns the global table `name`, creating it if needed.
+fn module_table(state: LuaState, name: &str) -> lua::Result<crate::OwnedRef> {
+    let _guard = StackGuard::new(state);
+    lua::reserve_stack(state, 3)?;
+    lua::push_special(state, lua::LuaSpecial::Glob);
+    lua::get_field(state, -1, name)?;
+    if !lua::is_type(state, -1, lua::LuaType::Table) {
+        lua::pop(state, 1);
+        lua::create_table(state);
+        lua::push_copy(state, -1);
+        lua::set_field(state, -3, name)?;
+    }
+    Ok(crate::OwnedRef::from_top_of_stack(state))
+}
@@ -0,0 +1,320 @@
+//! Lua stubs and API references generated from the native functions.
+//!
+//! [gmrs::function] records the name, parameters, return types and doc comments of every function
+//! and [gmrs::module] collects them into a `MODULE_INFO` constant. The types are mapped from the
+//! rust types, `f64` is a `number`, `Option<T>` is `T?`, `Vec<T>` is `T[]`, `MultiValue` is `...`
+//! and types gmrs doesnt know, like user data, keep their rust name. Functions outside of a module
+//! are taken with [gmrs::function_info] and collected with [Stubs], which can also be the `main`
+//! of a generator binary, see `examples/stubs.rs`.
+//!
+//! The files can also be generated from a test so they are updated with `cargo test`:
+//!
+//!```
+//! #[gmrs::module(name = "rust_arithmetic")]
+//! mod arithmetic {
+//!     use gmrs::prelude::*;
+//!
+//!     /// Adds two numbers.
+//!     #[function]
+//!     fn add(a: f64, b: f64) -> lua::Result<f64> {
+//!         Ok(a + b)
+//!     }
+//! }
+//!
+//! fn generate_stubs() -> std::io::Result<()> {
+//!     // writes `stubs/rust_arithmetic.lua` and `stubs/rust_arithmetic.md`
+//!     arithmetic::MODULE_INFO.write("stubs")
+//! }
+//!
+//! # fn main() {
+//! assert!(arithmetic::MODULE_INFO
+//!     .lua_stub()
+//!     .contains("---@param a number\n---@param b number\n---@return number\nfunction rust_arithmetic.add(a, b) end"));
+//! # }
+//!```
+use std::{fmt::Write, path::Path};
+
+/// The type of the parameters and returns that are any number of values, like
+/// [MultiValue](crate::lua::MultiValue). They are annotated with `---@vararg` and `...`.
+pub const VARARG: &str = "...";
+
+/// A function exported with [gmrs::function].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FunctionInfo {
+    pub name: &'static str,
+    pub docs: &'static str,
+    pub params: &'static [ParamInfo],
+    pub returns: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub ty: &'static str,
+}
+
+/// A constant registered with `#[constant]` in a [gmrs::module].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantInfo {
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub docs: &'static str,
+}
+
+/// The library table registered by [gmrs::module].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleInfo {
+    pub name: &'static str,
+    pub version: Option<&'static str>,
+    pub docs: &'static str,
+    pub functions: &'static [FunctionInfo],
+    pub constants: &'static [ConstantInfo],
+}
+
+impl ParamInfo {
+    /// The name used in the lua declaration, `...` for [VARARG] parameters.
+    pub fn lua_name(&self) -> &'static str {
+        if self.ty == VARARG {
+            VARARG
+        } else {
+            self.name
+        }
+    }
+}
+
+impl FunctionInfo {
+    /// The annotated declaration of the function, `table` is the table that contains it.
+    pub fn lua_stub(&self, table: Option<&str>) -> String {
+        let mut stub = String::new();
+        write_lua_docs(&mut stub, self.docs);
+        for param in self.params {
+            if param.ty == VARARG {
+                let _ = writeln!(stub, "---@vararg any");
+            } else {
+                let _ = writeln!(stub, "---@param {} {}", param.name, param.ty);
+            }
+        }
+        for ty in self.returns {
+            if *ty == VARARG {
+                let _ = writeln!(stub, "---@return any ...");
+            } else {
+                let _ = writeln!(stub, "---@return {}", ty);
+            }
+        }
+        let params: Vec<_> = self.params.iter().map(ParamInfo::lua_name).collect();
+        let _ = writeln!(
+            stub,
+            "function {}({}) end",
+            qualified_name(table, self.name),
+            params.join(", ")
+        );
+        stub
+    }
+
+    /// The signature used in the markdown reference, like `add(a: number, b: number) -> number`.
+    pub fn signature(&self, table: Option<&str>) -> String {
+        let params: Vec<_> = self
+            .params
+            .iter()
+            .map(|param| match param.ty {
+                VARARG => VARARG.to_string(),
+                ty => format!("{}: {}", param.name, ty),
+            })
+            .collect();
+        let mut signature = format!(
+            "{}({})",
+            qualified_name(table, self.name),
+            params.join(", ")
+        );
+        if !self.returns.is_empty() {
+            let _ = write!(signature, " -> {}", self.returns.join(", "));
+        }
+        signature
+    }
+}
+
+impl ModuleInfo {
+    /// A lua file with EmmyLua annotations declaring the table, its functions and constants.
+    pub fn lua_stub(&self) -> String {
+        let mut stub = String::from("---@meta\n\n");
+        write_lua_docs(&mut stub, self.docs);
+        let _ = writeln!(stub, "---@class {}", self.name);
+        let _ = writeln!(stub, "{} = {{}}", self.name);
+        if let Some(version) = self.version {
+            let _ = writeln!(stub, "\n---@type string");
+            let _ = writeln!(stub, "{}._VERSION = {:?}", self.name, version);
+        }
+        for constant in self.constants {
+            stub.push('\n');
+            write_lua_docs(&mut stub, constant.docs);
+            let _ = writeln!(stub, "---@type {}", constant.ty);
+            let _ = writeln!(stub, "{}.{} = nil", self.name, constant.name);
+        }
+        for function in self.functions {
+            stub.push('\n');
+            stub.push_str(&function.lua_stub(Some(self.name)));
+        }
+        stub
+    }
+
+    /// A markdown reference of the module.
+    pub fn markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.name);
+        if let Some(version) = self.version {
+            let _ = writeln!(markdown, "Version `{}`.", version);
+        }
+        if !self.docs.is_empty() {
+            let _ = writeln!(markdown, "\n{}", self.docs);
+        }
+        if !self.constants.is_empty() {
+            markdown
+                .push_str("\n## Constants\n| Name | Type | Description |\n| --- | --- | --- |\n");
+            for constant in self.constants {
+                let _ = writeln!(
+                    markdown,
+                    "| `{}.{}` | `{}` | {} |",
+                    self.name,
+                    constant.name,
+                    constant.ty,
+                    constant.docs.replace('\n', " ").replace('|', "\\|")
+                );
+            }
+        }
+        write_markdown_functions(&mut markdown, Some(self.name), self.functions);
+        markdown
+    }
+
+    /// Writes `<name>.lua` and `<name>.md` to `directory`, creating it if needed.
+    pub fn write<P: AsRef<Path>>(&self, directory: P) -> std::io::Result<()> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+        std::fs::write(
+            directory.join(format!("{}.lua", self.name)),
+            self.lua_stub(),
+        )?;
+        std::fs::write(directory.join(format!("{}.md", self.name)), self.markdown())
+    }
+}
+
+/// Every module and the functions registered outside of a module, like globals, of an addon.
+/// Used as the `main` of a stub generator binary:
+///
+///```no_run
+/// #[gmrs::function]
+/// fn greet(name: String) -> gmrs::lua::Result<String> {
+///     Ok(format!("hello {}", name))
+/// }
+///
+/// fn main() -> std::io::Result<()> {
+///     // writes `stubs/my_addon.lua` and `stubs/my_addon.md` or to the directory given as the
+///     // first argument
+///     gmrs::stubs::Stubs::new("my_addon")
+///         .function(gmrs::function_info!(greet))
+///         .generate()
+/// }
+///```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stubs {
+    name: String,
+    modules: Vec<ModuleInfo>,
+    functions: Vec<FunctionInfo>,
+}
+
+impl Stubs {
+    /// `name` is the name of the files with the functions outside of modules.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            modules: Vec::new(),
+            functions: Vec::new(),
+        }
+    }
+
+    /// Adds the `MODULE_INFO` of a [gmrs::module].
+    pub fn module(mut self, module: ModuleInfo) -> Self {
+        self.modules.push(module);
+        self
+    }
+
+    /// Adds a global function, its info is taken with [gmrs::function_info].
+    pub fn function(mut self, function: FunctionInfo) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    /// A lua file with EmmyLua annotations declaring the global functions.
+    pub fn lua_stub(&self) -> String {
+        let mut stub = String::from("---@meta\n");
+        for function in &self.functions {
+            stub.push('\n');
+            stub.push_str(&function.lua_stub(None));
+        }
+        stub
+    }
+
+    /// A markdown reference of the global functions.
+    pub fn markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.name);
+        write_markdown_functions(&mut markdown, None, &self.functions);
+        markdown
+    }
+
+    /// Writes the files of every module and `<name>.lua` and `<name>.md` with the global
+    /// functions to `directory`, creating it if needed.
+    pub fn write<P: AsRef<Path>>(&self, directory: P) -> std::io::Result<()> {
+        let directory = directory.as_ref();
+        for module in &self.modules {
+            module.write(directory)?;
+        }
+        if !self.functions.is_empty() {
+            std::fs::create_dir_all(directory)?;
+            std::fs::write(
+                directory.join(format!("{}.lua", self.name)),
+                self.lua_stub(),
+            )?;
+            std::fs::write(directory.join(format!("{}.md", self.name)), self.markdown())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the stubs to the directory passed as the first command line argument, `stubs` if
+    /// there is none.
+    pub fn generate(&self) -> std::io::Result<()> {
+        let directory = std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "stubs".to_string());
+        self.write(&directory)?;
+        println!("stubs written to '{}'", directory);
+        Ok(())
+    }
+}
+
+fn write_markdown_functions(
+    markdown: &mut String,
+    table: Option<&str>,
+    functions: &[FunctionInfo],
+) {
+    if functions.is_empty() {
+        return;
+    }
+    markdown.push_str("\n## Functions\n");
+    for function in functions {
+        let _ = writeln!(markdown, "\n### `{}`", function.signature(table));
+        if !function.docs.is_empty() {
+            let _ = writeln!(markdown, "{}", function.docs);
+        }
+    }
+}
+
+fn qualified_name(table: Option<&str>, name: &str) -> String {
+    match table {
+        Some(table) => format!("{}.{}", table, name),
+        None => name.to_string(),
+    }
+}
+
+fn write_lua_docs(stub: &mut String, docs: &str) {
+    for line in docs.lines() {
+        let _ = writeln!(stub, "---{}", line);
+    }
+}
@@ -0,0 +1,501 @@
+// Implements the functions of bridge.cpp over a plain Lua 5.1 state, used by the `test-backend`
+// feature so gmrs can be tested without the game.
+// Vector, Angle and Entity are userdata with a `MetaID` field in their metatable like in gmod.
+#include <stdbool.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "lauxlib.h"
+#include "lua.h"
+#include "lualib.h"
+
+typedef int (*CFunc)(lua_State*);
+
+enum
+{
+    TYPE_NONE   = -1,
+    TYPE_ENTITY = 9,
+    TYPE_VECTOR = 10,
+    TYPE_ANGLE  = 11,
+};
+
+enum
+{
+    SPECIAL_GLOB = 0,
+    SPECIAL_ENV  = 1,
+    SPECIAL_REG  = 2,
+};
+
+static int gmod_type(lua_State* state, int stack_pos)
+{
+    int type = lua_type(state, stack_pos);
+    if (type == LUA_TUSERDATA && lua_getmetatable(state, stack_pos))
+    {
+        lua_getfield(state, -1, "MetaID");
+        if (lua_isnumber(state, -1))
+            type = (int)lua_tointeger(state, -1);
+        lua_pop(state, 2);
+    }
+    return type;
+}
+
+static const char* gmod_type_name(int type)
+{
+    switch (type)
+    {
+    case TYPE_ENTITY:
+        return "Entity";
+    case TYPE_VECTOR:
+        return "Vector";
+    case TYPE_ANGLE:
+        return "Angle";
+    default:
+        // lua_typename doesnt use the state in 5.1
+        return type <= LUA_TTHREAD ? lua_typename(NULL, type) : "unknown";
+    }
+}
+
+// Vector and Angle
+
+static float* to_vector3(lua_State* state, int stack_pos, int type)
+{
+    if (gmod_type(state, stack_pos) != type)
+        return NULL;
+    return (float*)lua_touserdata(state, stack_pos);
+}
+
+static void push_vector3(lua_State* state, int type, float a, float b, float c)
+{
+    float* data = (float*)lua_newuserdata(state, sizeof(float) * 3);
+    data[0]     = a;
+    data[1]     = b;
+    data[2]     = c;
+    luaL_getmetatable(state, gmod_type_name(type));
+    lua_setmetatable(state, -2);
+}
+
+static float* check_vector3(lua_State* state, int stack_pos, int type)
+{
+    float* data = to_vector3(state, stack_pos, type);
+    if (data == NULL)
+        luaL_typerror(state, stack_pos, gmod_type_name(type));
+    return data;
+}
+
+/// Returns the index of the component named `key`, -1 if it isnt one.
+static int vector3_component(lua_State* state, int type, int key_pos)
+{
+    if (lua_type(state, key_pos) == LUA_TNUMBER)
+    {
+        int index = (int)lua_tointeger(state, key_pos);
+        return index >= 1 && index <= 3 ? index - 1 : -1;
+    }
+    if (lua_type(state, key_pos) != LUA_TSTRING)
+        return -1;
+    const char* key                = lua_tostring(state, key_pos);
+    static const char* vector[]    = {"x", "y", "z"};
+    static const char* angle[]     = {"p", "y", "r"};
+    static const char* angle_long[] = {"pitch", "yaw", "roll"};
+    for (int i = 0; i < 3; i++)
+    {
+        if (type == TYPE_VECTOR && strcmp(key, vector[i]) == 0)
+            return i;
+        if (type == TYPE_ANGLE && (strcmp(key, angle[i]) == 0 || strcmp(key, angle_long[i]) == 0))
+            return i;
+    }
+    return -1;
+}
+
+static int vector3_index(lua_State* state, int type)
+{
+    float* data   = check_vector3(state, 1, type);
+    int component = vector3_component(state, type, 2);
+    if (component >= 0)
+    {
+        lua_pushnumber(state, data[component]);
+        return 1;
+    }
+    // methods are looked up in the metatable
+    lua_getmetatable(state, 1);
+    lua_pushvalue(state, 2);
+    lua_rawget(state, -2);
+    return 1;
+}
+
+static int vector3_newindex(lua_State* state, int type)
+{
+    float* data   = check_vector3(state, 1, type);
+    int component = vector3_component(state, type, 2);
+    if (component < 0)
+        return luaL_error(state, "invalid %s component", gmod_type_name(type));
+    data[component] = (float)luaL_checknumber(state, 3);
+    return 0;
+}
+
+static int vector3_tostring(lua_State* state, int type)
+{
+    float* data = check_vector3(state, 1, type);
+    char buffer[128];
+    snprintf(buffer, sizeof(buffer), "%f %f %f", (double)data[0], (double)data[1], (double)data[2]);
+    lua_pushstring(state, buffer);
+    return 1;
+}
+
+static int vector3_eq(lua_State* state, int type)
+{
+    float* a = check_vector3(state, 1, type);
+    float* b = check_vector3(state, 2, type);
+    lua_pushboolean(state, a[0] == b[0] && a[1] == b[1] && a[2] == b[2]);
+    return 1;
+}
+
+static int vector3_add(lua_State* state, int type)
+{
+    float* a = check_vector3(state, 1, type);
+    float* b = check_vector3(state, 2, type);
+    push_vector3(state, type, a[0] + b[0], a[1] + b[1], a[2] + b[2]);
+    return 1;
+}
+
+static int vector3_sub(lua_State* state, int type)
+{
+    float* a = check_vector3(state, 1, type);
+    float* b = check_vector3(state, 2, type);
+    push_vector3(state, type, a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+    return 1;
+}
+
+static int vector3_unm(lua_State* state, int type)
+{
+    float* a = check_vector3(state, 1, type);
+    push_vector3(state, type, -a[0], -a[1], -a[2]);
+    return 1;
+}
+
+static int vector3_mul(lua_State* state, int type)
+{
+    if (lua_isnumber(state, 1))
+        lua_insert(state, 2);
+    float* a = check_vector3(state, 1, type);
+    if (lua_isnumber(state, 2))
+    {
+        float scalar = (float)lua_tonumber(state, 2);
+        push_vector3(state, type, a[0] * scalar, a[1] * scalar, a[2] * scalar);
+    }
+    else
+    {
+        float* b = check_vector3(state, 2, type);
+        push_vector3(state, type, a[0] * b[0], a[1] * b[1], a[2] * b[2]);
+    }
+    return 1;
+}
+
+static int vector3_div(lua_State* state, int type)
+{
+    float* a     = check_vector3(state, 1, type);
+    float scalar = (float)luaL_checknumber(state, 2);
+    push_vector3(state, type, a[0] / scalar, a[1] / scalar, a[2] / scalar);
+    return 1;
+}
+
+static int vector3_new(lua_State* state, int type)
+{
+    float* copy = to_vector3(state, 1, type);
+    if (copy != NULL)
+        push_vector3(state, type, copy[0], copy[1], copy[2]);
+    else
+        push_vector3(state, type, (float)luaL_optnumber(state, 1, 0.0), (float)luaL_optnumber(state, 2, 0.0),
+                     (float)luaL_optnumber(state, 3, 0.0));
+    return 1;
+}
+
+#define VECTOR3_FUNCTIONS(prefix, type)                                                                                \
+    static int prefix##_index(lua_State* state) { return vector3_index(state, type); }                                 \
+    static int prefix##_newindex(lua_State* state) { return vector3_newindex(state, type); }                           \
+    static int prefix##_tostring(lua_State* state) { return vector3_tostring(state, type); }                           \
+    static int prefix##_eq(lua_State* state) { return vector3_eq(state, type); }                                       \
+    static int prefix##_add(lua_State* state) { return vector3_add(state, type); }                                     \
+    static int prefix##_sub(lua_State* state) { return vector3_sub(state, type); }                                     \
+    static int prefix##_unm(lua_State* state) { return vector3_unm(state, type); }                                     \
+    static int prefix##_mul(lua_State* state) { return vector3_mul(state, type); }                                     \
+    static int prefix##_div(lua_State* state) { return vector3_div(state, type); }                                     \
+    static int prefix##_new(lua_State* state) { return vector3_new(state, type); }                                     \
+    static const luaL_Reg prefix##_meta[] = {                                                                          \
+        {"__index", prefix##_index}, {"__newindex", prefix##_newindex}, {"__tostring", prefix##_tostring},             \
+        {"__eq", prefix##_eq},       {"__add", prefix##_add},           {"__sub", prefix##_sub},                       \
+        {"__unm", prefix##_unm},     {"__mul", prefix##_mul},           {"__div", prefix##_div},                       \
+        {NULL, NULL}};
+
+VECTOR3_FUNCTIONS(vector, TYPE_VECTOR)
+VECTOR3_FUNCTIONS(angle, TYPE_ANGLE)
+
+// Entity, the fields are stored in the environment table of the userdata and the methods are
+// implemented in test_backend.lua
+
+static int entity_new(lua_State* state)
+{
+    lua_newuserdata(state, 1);
+    luaL_getmetatable(state, "Entity");
+    lua_setmetatable(state, -2);
+    lua_newtable(state);
+    lua_setfenv(state, -2);
+    return 1;
+}
+
+static void register_type(lua_State* state, int type, const luaL_Reg* meta)
+{
+    luaL_newmetatable(state, gmod_type_name(type));
+    lua_pushinteger(state, type);
+    lua_setfield(state, -2, "MetaID");
+    lua_pushstring(state, gmod_type_name(type));
+    lua_setfield(state, -2, "MetaName");
+    if (meta != NULL)
+        luaL_register(state, NULL, meta);
+    lua_pop(state, 1);
+}
+
+static int type_id(lua_State* state)
+{
+    luaL_checkany(state, 1);
+    lua_pushinteger(state, gmod_type(state, 1));
+    return 1;
+}
+
+/// Creates a state with the standard libraries and the gmod types, test_backend.lua should be
+/// run afterwards with the function from the registry field `gmrs_entity_new` as its argument.
+lua_State* gmod_test_backend_new_state(void)
+{
+    lua_State* state = luaL_newstate();
+    if (state == NULL)
+        return NULL;
+    luaL_openlibs(state);
+    register_type(state, TYPE_VECTOR, vector_meta);
+    register_type(state, TYPE_ANGLE, angle_meta);
+    register_type(state, TYPE_ENTITY, NULL);
+    lua_register(state, "Vector", vector_new);
+    lua_register(state, "Angle", angle_new);
+    lua_register(state, "TypeID", type_id);
+    lua_pushcfunction(state, entity_new);
+    lua_setfield(state, LUA_REGISTRYINDEX, "gmrs_entity_new");
+    return state;
+}
+
+void gmod_test_backend_close_state(lua_State* state)
+{
+    lua_close(state);
+}
+
+// bridge.cpp
+
+int gmod_bridge_top(lua_State* state)
+{
+    return lua_gettop(state);
+}
+void gmod_bridge_push(lua_State* state, int stack_pos)
+{
+    lua_pushvalue(state, stack_pos);
+}
+void gmod_bridge_pop(lua_State* state, int amount)
+{
+    lua_pop(state, amount);
+}
+void gmod_bridge_get_table(lua_State* state, int stack_pos)
+{
+    lua_gettable(state, stack_pos);
+}
+void gmod_bridge_get_field(lua_State* state, int stack_pos, const char* name)
+{
+    lua_getfield(state, stack_pos, name);
+}
+void gmod_bridge_set_field(lua_State* state, int stack_pos, const char* name)
+{
+    lua_setfield(state, stack_pos, name);
+}
+void gmod_bridge_create_table(lua_State* state)
+{
+    lua_newtable(state);
+}
+void gmod_bridge_set_table(lua_State* state, int i)
+{
+    lua_settable(state, i);
+}
+void gmod_bridge_set_meta_table(lua_State* state, int i)
+{
+    lua_setmetatable(state, i);
+}
+bool gmod_bridge_get_meta_table(lua_State* state, int i)
+{
+    return lua_getmetatable(state, i) != 0;
+}
+void gmod_bridge_call(lua_State* state, int args, int results)
+{
+    lua_call(state, args, results);
+}
+int gmod_bridge_pcall(lua_State* state, int args, int results, int error_func)
+{
+    return lua_pcall(state, args, results, error_func);
+}
+int gmod_bridge_equal(lua_State* state, int a, int b)
+{
+    return lua_equal(state, a, b);
+}
+int gmod_bridge_raw_equal(lua_State* state, int a, int b)
+{
+    return lua_rawequal(state, a, b);
+}
+void gmod_bridge_insert(lua_State* state, int stack_pos)
+{
+    lua_insert(state, stack_pos);
+}
+void gmod_bridge_remove(lua_State* state, int stack_pos)
+{
+    lua_remove(state, stack_pos);
+}
+int gmod_bridge_check_stack(lua_State* state, int extra)
+{
+    return lua_checkstack(state, extra) != 0;
+}
+int gmod_bridge_load_buffer(lua_State* state, const char* buffer, unsigned int len, const char* name)
+{
+    return luaL_loadbuffer(state, buffer, len, name);
+}
+int gmod_bridge_next(lua_State* state, int stack_pos)
+{
+    return lua_next(state, stack_pos);
+}
+void gmod_bridge_throw_error(lua_State* state, const char* error)
+{
+    lua_pushstring(state, error);
+    lua_error(state);
+}
+void gmod_bridge_check_type(lua_State* state, int stack_pos, int type)
+{
+    if (gmod_type(state, stack_pos) != type)
+        luaL_typerror(state, stack_pos, gmod_type_name(type));
+}
+void gmod_bridge_arg_error(lua_State* state, int arg_num, const char* msg)
+{
+    luaL_argerror(state, arg_num, msg);
+}
+void gmod_bridge_raw_get(lua_State* state, int stack_pos)
+{
+    lua_rawget(state, stack_pos);
+}
+void gmod_bridge_raw_set(lua_State* state, int stack_pos)
+{
+    lua_rawset(state, stack_pos);
+}
+
+const char* gmod_bridge_get_string(lua_State* state, int stack_pos, unsigned int* outlen)
+{
+    size_t len       = 0;
+    const char* data = lua_tolstring(state, stack_pos, &len);
+    if (outlen != NULL)
+        *outlen = (unsigned int)len;
+    return data;
+}
+double gmod_bridge_get_number(lua_State* state, int stack_pos)
+{
+    return lua_tonumber(state, stack_pos);
+}
+bool gmod_bridge_get_bool(lua_State* state, int stack_pos)
+{
+    return lua_toboolean(state, stack_pos) != 0;
+}
+CFunc gmod_bridge_get_c_function(lua_State* state, int stack_pos)
+{
+    return lua_tocfunction(state, stack_pos);
+}
+static void get_vector3(lua_State* state, int stack_pos, int type, float* out)
+{
+    float* data = to_vector3(state, stack_pos, type);
+    for (int i = 0; i < 3; i++)
+        out[i] = data != NULL ? data[i] : 0.0f;
+}
+void gmod_bridge_get_vector(lua_State* state, int stack_pos, float* vector)
+{
+    get_vector3(state, stack_pos, TYPE_VECTOR, vector);
+}
+void gmod_bridge_get_angle(lua_State* state, int stack_pos, float* angle)
+{
+    get_vector3(state, stack_pos, TYPE_ANGLE, angle);
+}
+
+void gmod_bridge_push_nil(lua_State* state)
+{
+    lua_pushnil(state);
+}
+void gmod_bridge_push_string(lua_State* state, const char* val, unsigned int len)
+{
+    lua_pushlstring(state, val, len);
+}
+void gmod_bridge_push_number(lua_State* state, double val)
+{
+    lua_pushnumber(state, val);
+}
+void gmod_bridge_push_bool(lua_State* state, bool val)
+{
+    lua_pushboolean(state, val);
+}
+void gmod_bridge_push_c_function(lua_State* state, CFunc val)
+{
+    lua_pushcfunction(state, val);
+}
+void gmod_bridge_push_c_closure(lua_State* state, CFunc val, int vars)
+{
+    lua_pushcclosure(state, val, vars);
+}
+void gmod_bridge_push_vector(lua_State* state, float x, float y, float z)
+{
+    push_vector3(state, TYPE_VECTOR, x, y, z);
+}
+void gmod_bridge_push_angle(lua_State* state, float p, float y, float r)
+{
+    push_vector3(state, TYPE_ANGLE, p, y, r);
+}
+
+int gmod_bridge_reference_create(lua_State* state)
+{
+    return luaL_ref(state, LUA_REGISTRYINDEX);
+}
+void gmod_bridge_reference_free(lua_State* state, int i)
+{
+    luaL_unref(state, LUA_REGISTRYINDEX, i);
+}
+void gmod_bridge_reference_push(lua_State* state, int i)
+{
+    lua_rawgeti(state, LUA_REGISTRYINDEX, i);
+}
+void gmod_bridge_push_special(lua_State* state, int special)
+{
+    switch (special)
+    {
+    // there is a single environment, the global table
+    case SPECIAL_GLOB:
+    case SPECIAL_ENV:
+        lua_pushvalue(state, LUA_GLOBALSINDEX);
+        break;
+    case SPECIAL_REG:
+        lua_pushvalue(state, LUA_REGISTRYINDEX);
+        break;
+    default:
+        lua_pushnil(state);
+        break;
+    }
+}
+bool gmod_bridge_is_type(lua_State* state, int stack_pos, int ty)
+{
+    return gmod_type(state, stack_pos) == ty;
+}
+int gmod_bridge_get_type(lua_State* state, int stack_pos)
+{
+    return gmod_type(state, stack_pos);
+}
+void* gmod_bridge_new_user_data(lua_State* state, unsigned int size)
+{
+    return lua_newuserdata(state, size);
+}
+void* gmod_bridge_get_user_data(lua_State* state, int stack_pos)
+{
+    return lua_touserdata(state, stack_pos);
+}
@@ -0,0 +1,614 @@
+-- Stand-ins for the parts of the gmod api used by gmrs, loaded by the test backend.
+-- Only the behaviour gmrs and the tests rely on is implemented.
+local entity_new = ...
+local registry = debug.getregistry()
+
+SERVER = true
+CLIENT = false
+
+function FindMetaTable(name)
+    return registry[name]
+end
+
+function isstring(value) return type(value) == "string" end
+function isnumber(value) return type(value) == "number" end
+function isbool(value) return type(value) == "boolean" end
+function istable(value) return type(value) == "table" end
+function isfunction(value) return type(value) == "function" end
+function isvector(value) return TypeID(value) == 10 end
+function isangle(value) return TypeID(value) == 11 end
+function isentity(value) return TypeID(value) == 9 end
+
+function IsValid(value)
+    if value == nil or type(value) == "boolean" or type(value) == "number" or type(value) == "string" then
+        return false
+    end
+    local is_valid = value.IsValid
+    return is_valid ~= nil and is_valid(value) == true
+end
+
+-- console output, everything is written with the registry function `gmrs_write_output` so it can
+-- be captured
+
+function registry.gmrs_write_output(text)
+    io.write(text)
+end
+
+local function write_output(text)
+    registry.gmrs_write_output(text)
+end
+
+local function concat_args(separator, skip_colors, ...)
+    local parts = {}
+    for i = 1, select("#", ...) do
+        local value = select(i, ...)
+        if not (skip_colors and IsColor(value)) then
+            parts[#parts + 1] = tostring(value)
+        end
+    end
+    return table.concat(parts, separator)
+end
+
+function print(...)
+    write_output(concat_args("\t", false, ...) .. "\n")
+end
+
+function Msg(...)
+    write_output(concat_args("", false, ...))
+end
+
+-- colors are ignored
+function MsgC(...)
+    write_output(concat_args("", true, ...))
+end
+
+function ErrorNoHalt(...)
+    write_output(concat_args("", false, ...))
+end
+
+-- Color
+
+local COLOR = {MetaName = "Color"}
+COLOR.__index = COLOR
+registry.Color = COLOR
+
+function COLOR:__tostring()
+    return string.format("%d %d %d %d", self.r, self.g, self.b, self.a)
+end
+
+function COLOR.__eq(a, b)
+    return a.r == b.r and a.g == b.g and a.b == b.b and a.a == b.a
+end
+
+function Color(r, g, b, a)
+    return setmetatable({
+        r = math.min(tonumber(r) or 255, 255),
+        g = math.min(tonumber(g) or 255, 255),
+        b = math.min(tonumber(b) or 255, 255),
+        a = math.min(tonumber(a) or 255, 255),
+    }, COLOR)
+end
+
+function IsColor(value)
+    return type(value) == "table" and getmetatable(value) == COLOR
+end
+
+-- hook
+
+hook = {}
+local hooks = {}
+
+function hook.Add(event, id, func)
+    if not isfunction(func) or id == nil then
+        return
+    end
+    hooks[event] = hooks[event] or {}
+    hooks[event][id] = func
+end
+
+function hook.Remove(event, id)
+    if hooks[event] then
+        hooks[event][id] = nil
+    end
+end
+
+function hook.GetTable()
+    return hooks
+end
+
+function hook.Call(event, gm, ...)
+    local event_hooks = hooks[event]
+    if event_hooks then
+        for id, func in pairs(event_hooks) do
+            local a, b, c, d, e, f
+            if isstring(id) then
+                a, b, c, d, e, f = func(...)
+            elseif IsValid(id) then
+                a, b, c, d, e, f = func(id, ...)
+            else
+                event_hooks[id] = nil
+            end
+            if a ~= nil then
+                return a, b, c, d, e, f
+            end
+        end
+    end
+    if gm and gm[event] then
+        return gm[event](gm, ...)
+    end
+end
+
+function hook.Run(event, ...)
+    return hook.Call(event, GAMEMODE, ...)
+end
+
+-- Entity and Player, the fields of an entity are stored in the environment of its userdata
+
+local ENTITY = FindMetaTable("Entity")
+local PLAYER = {MetaName = "Player", MetaID = 9}
+registry.Player = PLAYER
+
+local entities = {}
+local next_index = 1
+
+local function data(entity)
+    return debug.getfenv(entity)
+end
+
+local function check(entity)
+    local entity_data = data(entity)
+    if not entity_data.valid then
+        error("Tried to use a NULL entity!", 3)
+    end
+    return entity_data
+end
+
+function ENTITY:__index(key)
+    local entity_data = data(self)
+    if entity_data.is_player and PLAYER[key] ~= nil then
+        return PLAYER[key]
+    end
+    local method = rawget(ENTITY, key)
+    if method ~= nil then
+        return method
+    end
+    if entity_data.table then
+        return entity_data.table[key]
+    end
+end
+
+function ENTITY:__newindex(key, value)
+    check(self).table[key] = value
+end
+
+function ENTITY:__tostring()
+    local entity_data = data(self)
+    if not entity_data.valid then
+        return "[NULL Entity]"
+    elseif entity_data.is_player then
+        return string.format("Player [%d][%s]", entity_data.index, entity_data.nick)
+    end
+    return string.format("Entity [%d][%s]", entity_data.index, entity_data.class)
+end
+
+local function create_entity(class)
+    local entity = entity_new()
+    local entity_data = data(entity)
+    entity_data.valid = true
+    entity_data.index = next_index
+    entity_data.class = class
+    entity_data.pos = Vector()
+    entity_data.angles = Angle()
+    entity_data.health = 0
+    entity_data.table = {}
+    entity_data.nw = {}
+    entities[next_index] = entity
+    next_index = next_index + 1
+    return entity
+end
+
+NULL = entity_new()
+data(NULL).valid = false
+
+function ENTITY:IsValid() return data(self).valid end
+function ENTITY:EntIndex() return data(self).index or 0 end
+function ENTITY:GetClass() return check(self).class end
+function ENTITY:IsPlayer() return data(self).is_player == true end
+function ENTITY:GetPos() return Vector(check(self).pos) end
+function ENTITY:SetPos(pos) check(self).pos = Vector(pos) end
+function ENTITY:GetAngles() return Angle(check(self).angles) end
+function ENTITY:SetAngles(angles) check(self).angles = Angle(angles) end
+function ENTITY:Health() return check(self).health end
+function ENTITY:SetHealth(health) check(self).health = health end
+function ENTITY:GetModel() return check(self).model end
+function ENTITY:SetModel(model) check(self).model = model end
+function ENTITY:GetTable() return check(self).table end
+
+function ENTITY:Remove()
+    local entity_data = check(self)
+    entities[entity_data.index] = nil
+    entity_data.valid = false
+end
+
+local nw_defaults = {Int = 0, Float = 0, Bool = false, String = "", Entity = NULL, Vector = Vector(), Angle = Angle()}
+for name, default in pairs(nw_defaults) do
+    ENTITY["GetNW" .. name] = function(self, key, fallback)
+        local value = check(self).nw[key]
+        if value == nil then
+            if fallback ~= nil then return fallback end
+            return default
+        end
+        return value
+    end
+    ENTITY["SetNW" .. name] = function(self, key, value)
+        check(self).nw[key] = value
+    end
+end
+
+function PLAYER:Nick() return check(self).nick end
+PLAYER.Name = PLAYER.Nick
+PLAYER.GetName = PLAYER.Nick
+function PLAYER:SteamID64() return check(self).steam_id64 end
+function PLAYER:IsBot() return check(self).is_bot end
+function PLAYER:GetUserGroup() return check(self).user_group end
+function PLAYER:SetUserGroup(group) check(self).user_group = group end
+function PLAYER:IsUserGroup(group) return check(self).user_group == group end
+function PLAYER:IsSuperAdmin() return self:IsUserGroup("superadmin") end
+function PLAYER:IsAdmin() return self:IsUserGroup("admin") or self:IsSuperAdmin() end
+
+HUD_PRINTNOTIFY = 1
+HUD_PRINTCONSOLE = 2
+HUD_PRINTTALK = 3
+HUD_PRINTCENTER = 4
+
+function PLAYER:PrintMessage(kind, message)
+    local printed = check(self).printed
+    printed[#printed + 1] = {kind = kind, message = tostring(message)}
+end
+
+function PLAYER:ChatPrint(message)
+    self:PrintMessage(HUD_PRINTTALK, message)
+end
+
+-- not part of gmod, returns the messages sent with PrintMessage and ChatPrint
+function PLAYER:GetPrintedMessages()
+    return check(self).printed
+end
+
+ents = {}
+
+function ents.Create(class)
+    return create_entity(class)
+end
+
+function ents.GetAll()
+    local list = {}
+    for _, entity in pairs(entities) do
+        list[#list + 1] = entity
+    end
+    table.sort(list, function(a, b) return a:EntIndex() < b:EntIndex() end)
+    return list
+end
+
+function ents.GetByIndex(index)
+    return entities[index] or NULL
+end
+
+function Entity(index)
+    return ents.GetByIndex(index)
+end
+
+player = {}
+
+function player.CreateNextBot(nick)
+    local ply = create_entity("player")
+    local ply_data = data(ply)
+    ply_data.is_player = true
+    ply_data.is_bot = true
+    ply_data.nick = nick
+    ply_data.steam_id64 = "765611979602" .. tostring(65728 + ply_data.index)
+    ply_data.user_group = "user"
+    ply_data.health = 100
+    ply_data.printed = {}
+    return ply
+end
+
+function player.GetAll()
+    local list = {}
+    for _, entity in ipairs(ents.GetAll()) do
+        if entity:IsPlayer() then
+            list[#list + 1] = entity
+        end
+    end
+    return list
+end
+
+function player.GetCount()
+    return #player.GetAll()
+end
+
+-- concommand
+
+concommand = {}
+local commands = {}
+local completions = {}
+
+function concommand.Add(name, callback, autocomplete, help, flags)
+    name = name:lower()
+    commands[name] = callback
+    completions[name] = autocomplete
+end
+
+function concommand.Remove(name)
+    name = name:lower()
+    commands[name] = nil
+    completions[name] = nil
+end
+
+function concommand.GetTable()
+    return commands, completions
+end
+
+function concommand.Run(ply, cmd, args, arg_str)
+    local callback = commands[cmd:lower()]
+    if callback == nil then
+        return false
+    end
+    callback(ply, cmd, args, arg_str)
+    return true
+end
+
+-- ConVar, the change callbacks run right away instead of the next frame
+
+local CONVAR = {MetaName = "ConVar"}
+CONVAR.__index = CONVAR
+registry.ConVar = CONVAR
+
+local convars = {}
+local change_callbacks = {}
+
+function CreateConVar(name, default, flags, help)
+    local convar = convars[name:lower()]
+    if convar then
+        return convar
+    end
+    convar = setmetatable({
+        name = name,
+        default = tostring(default),
+        value = tostring(default),
+        help = help or "",
+    }, CONVAR)
+    convars[name:lower()] = convar
+    return convar
+end
+
+function GetConVar(name)
+    return convars[name:lower()]
+end
+
+function CONVAR:GetName() return self.name end
+function CONVAR:GetDefault() return self.default end
+function CONVAR:GetHelpText() return self.help end
+function CONVAR:GetString() return self.value end
+function CONVAR:GetFloat() return tonumber(self.value) or 0 end
+function CONVAR:GetInt() return math.floor(self:GetFloat()) end
+function CONVAR:GetBool() return self:GetInt() ~= 0 end
+
+function CONVAR:SetString(value)
+    local old = self.value
+    self.value = tostring(value)
+    if old == self.value then
+        return
+    end
+    for _, callback in pairs(change_callbacks[self.name:lower()] or {}) do
+        callback(self.name, old, self.value)
+    end
+end
+
+CONVAR.SetFloat = CONVAR.SetString
+CONVAR.SetInt = CONVAR.SetString
+
+function CONVAR:SetBool(value)
+    self:SetString(value and "1" or "0")
+end
+
+cvars = {}
+
+function cvars.AddChangeCallback(name, callback, identifier)
+    name = name:lower()
+    change_callbacks[name] = change_callbacks[name] or {}
+    local callbacks = change_callbacks[name]
+    callbacks[identifier or #callbacks + 1] = callback
+end
+
+function cvars.RemoveChangeCallback(name, identifier)
+    local callbacks = change_callbacks[name:lower()]
+    if callbacks then
+        callbacks[identifier] = nil
+    end
+end
+
+function cvars.GetConVarCallbacks(name)
+    return change_callbacks[name:lower()] or {}
+end
+
+-- runs the command as the server console, setting the convar if there is no command
+function RunConsoleCommand(cmd, ...)
+    local args = {}
+    for i = 1, select("#", ...) do
+        args[i] = tostring(select(i, ...))
+    end
+    if concommand.Run(NULL, cmd, args, table.concat(args, " ")) then
+        return
+    end
+    local convar = GetConVar(cmd)
+    if convar and args[1] ~= nil then
+        convar:SetString(args[1])
+    end
+end
+
+-- net, messages are kept in memory instead of being sent
+
+net = {}
+net.Receivers = {}
+util = {}
+
+local network_strings = {}
+local outgoing
+local incoming
+local sent = {}
+
+function util.AddNetworkString(name)
+    network_strings[name:lower()] = true
+end
+
+function util.NetworkStringToID(name)
+    return network_strings[name:lower()] and 1 or 0
+end
+
+function net.Start(name, unreliable)
+    if not network_strings[name:lower()] then
+        error("Calling net.Start with unpooled message name!", 2)
+    end
+    outgoing = {name = name, unreliable = unreliable == true, values = {}, bits = 0}
+end
+
+function net.Receive(name, callback)
+    net.Receivers[name:lower()] = callback
+end
+
+local function write(kind, bits, value)
+    if outgoing == nil then
+        error("net.Write" .. kind .. " called without net.Start", 3)
+    end
+    outgoing.values[#outgoing.values + 1] = {kind = kind, value = value, bits = bits}
+    outgoing.bits = outgoing.bits + bits
+end
+
+-- values read past the end of the message are zero, like in gmod, the sizes given when reading
+-- are ignored and the values are returned as they were written
+local function read(kind, zero)
+    if incoming == nil then
+        error("net.Read" .. kind .. " called outside of a receiver", 3)
+    end
+    local entry = incoming.values[incoming.position]
+    if entry == nil then
+        return zero
+    end
+    if entry.kind ~= kind then
+        error(string.format("net.Read%s called but the next value was written with net.Write%s", kind, entry.kind), 3)
+    end
+    incoming.position = incoming.position + 1
+    incoming.bits_left = incoming.bits_left - entry.bits
+    return entry.value
+end
+
+local function wrap(value, bits, signed)
+    value = math.floor(tonumber(value) or 0) % 2 ^ bits
+    if signed and value >= 2 ^ (bits - 1) then
+        value = value - 2 ^ bits
+    end
+    return value
+end
+
+function net.WriteUInt(value, bits) write("UInt", bits, wrap(value, bits, false)) end
+function net.WriteInt(value, bits) write("Int", bits, wrap(value, bits, true)) end
+function net.WriteBool(value) write("Bool", 1, value == true) end
+function net.WriteFloat(value) write("Float", 32, value) end
+function net.WriteDouble(value) write("Double", 64, value) end
+function net.WriteVector(value) write("Vector", 96, Vector(value)) end
+function net.WriteAngle(value) write("Angle", 96, Angle(value)) end
+function net.WriteEntity(value) write("Entity", 16, value) end
+
+-- strings are cut at the first null byte
+function net.WriteString(value)
+    value = tostring(value):match("^[^%z]*")
+    write("String", (#value + 1) * 8, value)
+end
+
+function net.WriteData(data, length)
+    data = data:sub(1, length)
+    write("Data", #data * 8, data)
+end
+
+function net.WriteColor(color, write_alpha)
+    write("Color", write_alpha == false and 24 or 32, Color(color.r, color.g, color.b, write_alpha == false and 255 or color.a))
+end
+
+function net.ReadUInt(bits) return read("UInt", 0) end
+function net.ReadInt(bits) return read("Int", 0) end
+function net.ReadBool() return read("Bool", false) end
+function net.ReadFloat() return read("Float", 0) end
+function net.ReadDouble() return read("Double", 0) end
+function net.ReadVector() return Vector(read("Vector", Vector())) end
+function net.ReadAngle() return Angle(read("Angle", Angle())) end
+function net.ReadString() return read("String", "") end
+
+function net.ReadData(length)
+    return read("Data", ""):sub(1, length)
+end
+
+function net.ReadColor(read_alpha)
+    local color = read("Color", Color(0, 0, 0, 0))
+    return Color(color.r, color.g, color.b, color.a)
+end
+
+function net.ReadEntity()
+    local entity = read("Entity", NULL)
+    if IsValid(entity) then
+        return entity
+    end
+    return NULL
+end
+
+function net.BytesWritten()
+    if outgoing == nil then
+        return nil
+    end
+    return math.ceil(outgoing.bits / 8), outgoing.bits
+end
+
+function net.BytesLeft()
+    if incoming == nil then
+        return nil
+    end
+    return math.ceil(incoming.bits_left / 8), incoming.bits_left
+end
+
+local function send(target)
+    if outgoing == nil then
+        error("net.Send called without net.Start", 3)
+    end
+    outgoing.target = target
+    sent[#sent + 1] = outgoing
+    outgoing = nil
+end
+
+function net.Send(target) send(target) end
+function net.SendOmit(target) send({omit = target}) end
+function net.SendPVS(pos) send({pvs = pos}) end
+function net.Broadcast() send("broadcast") end
+function net.SendToServer() send("server") end
+
+-- not part of gmod, returns the messages sent and forgets them
+function net.TakeSent()
+    local messages = sent
+    sent = {}
+    return messages
+end
+
+-- not part of gmod, runs the receiver of a sent message as if `ply` had sent it
+function net.Deliver(message, ply)
+    local receiver = net.Receivers[message.name:lower()]
+    if receiver == nil then
+        return false
+    end
+    local previous = incoming
+    incoming = {values = message.values, position = 1, bits_left = message.bits}
+    local ok, err = pcall(receiver, message.bits, ply)
+    incoming = previous
+    if not ok then
+        error(err, 2)
+    end
+    return true
+end
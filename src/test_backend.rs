@@ -0,0 +1,99 @@
+//! A lua state that runs inside the test process, enabled with the `test-backend` feature.
+//!
+//! With the feature enabled the bridge is implemented over a vendored Lua 5.1 instead of the
+//! game, so everything in gmrs works without gmod. The state has stand-ins for `Vector`, `Angle`,
+//! `Entity`, `Player`, `Color`, `hook`, `ents.Create`, `player.CreateNextBot`, `concommand`,
+//! convars, `net` and the console functions, check `src/test_backend.lua` for what is available.
+//! Net messages arent sent anywhere, `net.TakeSent()` returns them and `net.Deliver(message, ply)`
+//! runs their receiver.
+//!
+//!```
+//! use gmrs::prelude::*;
+//! use gmrs::test_backend::TestState;
+//!
+//! #[gmrs::function]
+//! fn add(a: f64, b: f64) -> lua::Result<f64> {
+//!     Ok(a + b)
+//! }
+//!
+//! let lua = TestState::new();
+//! gmrs::set_global(lua.state(), "add", lua::NativeFunc::new(add)).unwrap();
+//! assert_eq!(lua.eval::<f64>("add(1, 2)").unwrap(), 3.0);
+//! assert!(lua.run("add('a', 2)").is_err());
+//!```
+use crate::{
+    internal,
+    lua::{self, FromStack, LuaSpecial, LuaState, LuaStateRaw, StackGuard},
+};
+
+const PRELUDE: &str = include_str!("test_backend.lua");
+
+extern "C" {
+    fn gmod_test_backend_new_state() -> LuaStateRaw;
+    fn gmod_test_backend_close_state(state: LuaStateRaw);
+}
+
+/// Owns a lua state, the state is closed when dropped.
+/// While it is alive the state is the current state of the thread that created it, see
+/// [crate::get_lua_state].
+#[derive(Debug)]
+pub struct TestState {
+    state: LuaState,
+}
+
+impl TestState {
+    /// Creates a new state with the gmod stand-ins loaded.
+    pub fn new() -> Self {
+        let raw = unsafe { gmod_test_backend_new_state() };
+        assert!(!raw.is_null(), "failed to allocate the lua state");
+        let state = unsafe { LuaState::new(raw) };
+        let test_state = Self { state };
+        {
+            let _guard = StackGuard::new(state);
+            lua::load_buffer(state, PRELUDE, "=test_backend.lua").unwrap();
+            lua::push_special(state, LuaSpecial::Reg);
+            lua::get_field(state, -1, crate::field!("gmrs_entity_new"));
+            lua::remove(state, -2);
+            lua::pcall_result(state, 1, 0).unwrap();
+        }
+        unsafe { internal::set_lua_state_raw(raw) };
+        test_state
+    }
+
+    pub fn state(&self) -> LuaState {
+        self.state
+    }
+
+    /// Runs `code` as a lua chunk.
+    pub fn run(&self, code: &str) -> lua::Result<()> {
+        let _guard = StackGuard::new(self.state);
+        lua::load_buffer(self.state, code, "=test")?;
+        lua::pcall_result(self.state, 0, 0)
+    }
+
+    /// Evaluates the lua expression `expression` and converts the result to `T`.
+    pub fn eval<T: FromStack>(&self, expression: &str) -> lua::Result<T> {
+        let _guard = StackGuard::new(self.state);
+        let top = lua::top(self.state);
+        lua::load_buffer(self.state, format!("return {}", expression), "=test")?;
+        lua::pcall_result(self.state, 0, lua::MULT_RET)?;
+        lua::get(self.state, top + 1)
+    }
+}
+
+impl Default for TestState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TestState {
+    fn drop(&mut self) {
+        if internal::get_lua_state() == Some(self.state) {
+            // the cached functions belong to this state
+            crate::globals::clear_cache(self.state);
+            internal::unset_lua_state_raw();
+        }
+        unsafe { gmod_test_backend_close_state(self.state.ptr()) };
+    }
+}
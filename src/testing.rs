@@ -0,0 +1,218 @@
+//! Drives a module's lifecycle inside tests, enabled with the `test-backend` feature.
+//!
+//! A [TestServer] opens the module by calling the function generated by [gmrs::entry], runs lua
+//! code, advances `Think` ticks to execute the closures queued by other threads and captures
+//! everything printed to the console. The module is closed with the function generated by
+//! [gmrs::exit] when the server is dropped.
+//!
+//! Every server opens the module in its own state, [crate::remote_execute] runs in the first one
+//! still open so use [crate::remote_execute_on] with [TestServer::state_id] when tests run in
+//! parallel.
+//!
+//!```
+//! use gmrs::prelude::*;
+//!
+//! #[gmrs::function]
+//! fn greet(name: String) -> lua::Result<String> {
+//!     Ok(format!("hello {}", name))
+//! }
+//!
+//! #[gmrs::entry]
+//! fn open(state: LuaState) {
+//!     gmrs::set_global(state, "greet", NativeFunc::new(greet)).unwrap();
+//!     gmrs::print!(state, "module loaded");
+//! }
+//!
+//! #[gmrs::exit]
+//! fn close(_state: LuaState) {}
+//!
+//! fn main() {
+//!     let server = gmrs::test_server!();
+//!     server.assert_output_contains("module loaded");
+//!     server.run("result = greet('world')").unwrap();
+//!     server.assert_global("result", "hello world".to_string());
+//!
+//!     let id = server.state_id();
+//!     std::thread::spawn(move || {
+//!         gmrs::remote_execute_on(id, |state| gmrs::print(state, "from a thread"))
+//!     });
+//!     assert!(server.think_until(std::time::Duration::from_secs(5), |server| {
+//!         server.output().contains("from a thread")
+//!     }));
+//! }
+//!```
+use crate::{
+    internal,
+    lua::{self, FromStack, LuaSpecial, LuaState, LuaStateRaw, StackGuard},
+    test_backend::TestState,
+};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Signature of `gmod13_open` and `gmod13_close`.
+pub type ModuleFunction = extern "C" fn(LuaStateRaw) -> u32;
+
+/// Creates a [TestServer] using the `gmod13_open` and `gmod13_close` functions generated by
+/// [gmrs::entry] and [gmrs::exit] in the root of the current crate.
+/// The functions can also be given explicitly with `test_server!(open, close)`.
+#[macro_export]
+// `crate` is meant to refer to the crate calling the macro
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! test_server {
+    () => {
+        $crate::testing::TestServer::start(crate::gmod13_open, crate::gmod13_close)
+    };
+    ($open:expr, $close:expr) => {
+        $crate::testing::TestServer::start($open, $close)
+    };
+}
+
+/// A lua state with a module loaded, see the [module documentation](self).
+pub struct TestServer {
+    lua: TestState,
+    output: Arc<Mutex<String>>,
+    close: Option<ModuleFunction>,
+}
+
+impl TestServer {
+    /// Creates a new state, starts capturing its output and opens the module with `open`.
+    pub fn start(open: ModuleFunction, close: ModuleFunction) -> Self {
+        let lua = TestState::new();
+        let output = Arc::new(Mutex::new(String::new()));
+        capture_output(lua.state(), output.clone());
+        open(lua.state().ptr());
+        // the module functions unset the state when they return, this thread is the lua thread
+        unsafe { internal::set_lua_state_raw(lua.state().ptr()) };
+        Self {
+            lua,
+            output,
+            close: Some(close),
+        }
+    }
+
+    pub fn state(&self) -> LuaState {
+        self.lua.state()
+    }
+
+    /// The id of the server's state.
+    pub fn state_id(&self) -> crate::StateId {
+        internal::state_id(self.state())
+    }
+
+    /// Runs `code` as a lua chunk.
+    pub fn run(&self, code: &str) -> lua::Result<()> {
+        self.lua.run(code)
+    }
+
+    /// Evaluates the lua expression `expression` and converts the result to `T`.
+    pub fn eval<T: FromStack>(&self, expression: &str) -> lua::Result<T> {
+        self.lua.eval(expression)
+    }
+
+    /// Returns the global `name` converted to `T`.
+    pub fn global<T: FromStack>(&self, name: &str) -> lua::Result<T> {
+        let state = self.state();
+        let _guard = StackGuard::new(state);
+        lua::push_special(state, LuaSpecial::Glob);
+        lua::get_field(state, -1, name)?;
+        lua::get(state, -1)
+    }
+
+    /// Panics if the global `name` is not `expected`.
+    pub fn assert_global<T>(&self, name: &str, expected: T)
+    where
+        T: FromStack + PartialEq + Debug,
+    {
+        match self.global::<T>(name) {
+            Ok(value) => assert_eq!(value, expected, "unexpected value of the global '{}'", name),
+            Err(e) => panic!("failed to get the global '{}': {}", name, e),
+        }
+    }
+
+    /// Calls `hook.Run("Think")` once, this executes the closures queued by other threads.
+    pub fn think(&self) -> lua::Result<()> {
+        self.run("hook.Run('Think')")
+    }
+
+    /// Calls [TestServer::think] `ticks` times.
+    pub fn think_for(&self, ticks: u32) -> lua::Result<()> {
+        for _ in 0..ticks {
+            self.think()?;
+        }
+        Ok(())
+    }
+
+    /// Calls [TestServer::think] until `condition` returns true or `timeout` passes.
+    /// Returns the last result of `condition`, errors while thinking are ignored.
+    pub fn think_until<F>(&self, timeout: Duration, mut condition: F) -> bool
+    where
+        F: FnMut(&Self) -> bool,
+    {
+        let start = Instant::now();
+        loop {
+            let _ = self.think();
+            if condition(self) {
+                return true;
+            }
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Everything printed since the server started or since the last [TestServer::take_output].
+    pub fn output(&self) -> String {
+        self.output.lock().unwrap().clone()
+    }
+
+    /// Returns the output and clears it.
+    pub fn take_output(&self) -> String {
+        std::mem::take(&mut *self.output.lock().unwrap())
+    }
+
+    /// Panics if `text` wasnt printed.
+    pub fn assert_output_contains(&self, text: &str) {
+        let output = self.output();
+        assert!(
+            output.contains(text),
+            "expected the output to contain {:?}, the output was:\n{}",
+            text,
+            output
+        );
+    }
+
+    /// Closes the module, also done when the server is dropped.
+    pub fn close(mut self) {
+        self.close_module();
+    }
+
+    fn close_module(&mut self) {
+        if let Some(close) = self.close.take() {
+            close(self.state().ptr());
+            unsafe { internal::set_lua_state_raw(self.state().ptr()) };
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.close_module();
+    }
+}
+
+/// Replaces the function used by the test backend to print with one that appends to `output`.
+fn capture_output(state: LuaState, output: Arc<Mutex<String>>) {
+    let _guard = StackGuard::new(state);
+    lua::push_special(state, LuaSpecial::Reg);
+    lua::push_closure(state, move |state| {
+        let text: Vec<u8> = lua::get(state, 1)?;
+        let text = String::from_utf8_lossy(&text);
+        output.lock().unwrap().push_str(&text);
+        Ok(())
+    });
+    lua::set_field(state, -2, crate::field!("gmrs_write_output"));
+}
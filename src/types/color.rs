@@ -0,0 +1,139 @@
+use crate::lua::{self, FromTable, LuaSpecial, LuaState, TableView, ToStack};
+
+/// Gmod's `Color`.
+/// Can be created from any table with `r`, `g`, `b` and optionally `a` fields and is
+/// pushed using the global `Color` function so it has the `Color` metatable.
+///
+///```
+///# use gmrs::prelude::*;
+/// let orange = Color::from_hex("#ff8000").unwrap();
+/// assert_eq!(orange, Color::rgb(255, 128, 0));
+/// assert_eq!(orange.to_hex(), "#ff8000");
+/// assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::rgb(0, 255, 0));
+///```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const RED: Color = Color::rgb(255, 0, 0);
+    pub const GREEN: Color = Color::rgb(0, 255, 0);
+    pub const BLUE: Color = Color::rgb(0, 0, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Creates an opaque color.
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, 255)
+    }
+
+    /// Parses `#rrggbb` or `#rrggbbaa`, the `#` is optional.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return None;
+        }
+        let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let a = if hex.len() == 8 { component(6)? } else { 255 };
+        Some(Self::new(component(0)?, component(2)?, component(4)?, a))
+    }
+
+    /// Formats the color as `#rrggbb`, ignoring alpha.
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Formats the color as `#rrggbbaa`.
+    pub fn to_hex_rgba(self) -> String {
+        format!("{}{:02x}", self.to_hex(), self.a)
+    }
+
+    /// Same as gmod's `HSVToColor`. `hue` is in degrees, `saturation` and `value` go from 0 to 1.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let hue = hue.rem_euclid(360.0);
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+        let (r, g, b) = match (hue / 60.0) as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let to_u8 = |c: f32| ((c + m) * 255.0).round() as u8;
+        Self::rgb(to_u8(r), to_u8(g), to_u8(b))
+    }
+
+    /// Same as gmod's `ColorToHSV`, returns `(hue, saturation, value)`.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { delta / max };
+        (hue, saturation, max)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl FromTable for Color {
+    fn from_table(state: LuaState, tbl: TableView) -> lua::Result<Self> {
+        Ok(Self::new(
+            tbl.get(state, "r")?,
+            tbl.get(state, "g")?,
+            tbl.get(state, "b")?,
+            tbl.get::<Option<u8>>(state, "a")?.unwrap_or(255),
+        ))
+    }
+}
+
+impl ToStack for Color {
+    fn push(self, state: LuaState) -> i32 {
+        lua::push_special(state, LuaSpecial::Glob);
+        lua::get_field(state, -1, crate::field!("Color"));
+        lua::remove(state, -2);
+        let result = lua::pcall_result_with(state, 1, |state| {
+            lua::push(state, self.r);
+            lua::push(state, self.g);
+            lua::push(state, self.b);
+            lua::push(state, self.a);
+        });
+        if result.is_err() {
+            // `Color` is not available, push a plain table instead
+            let tbl = lua::create_table(state);
+            tbl.set(state, "r", self.r);
+            tbl.set(state, "g", self.g);
+            tbl.set(state, "b", self.b);
+            tbl.set(state, "a", self.a);
+        }
+        1
+    }
+}
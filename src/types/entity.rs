@@ -0,0 +1,229 @@
+use super::{Angle, Vector};
+use crate::{
+    lua::{self, FromStack, LuaState, LuaType, StackGuard, ToStack},
+    ArcRef,
+};
+use std::ops::Deref;
+
+/// A reference to a gmod entity.
+/// The entity can be removed while we hold the reference, use [Entity::is_valid] to check.
+///
+///```
+///# use gmrs::prelude::*;
+/// #[gmrs::function]
+/// fn teleport_up(state: LuaState, ent: Entity, height: f32) -> lua::Result<()> {
+///     let pos = ent.pos(state)?;
+///     ent.set_pos(state, pos + Vector::new(0.0, 0.0, height))
+/// }
+///```
+#[derive(Debug, Clone)]
+pub struct Entity(ArcRef);
+
+/// A reference to a player entity, derefs to [Entity].
+#[derive(Debug, Clone)]
+pub struct Player(Entity);
+
+macro_rules! impl_nw_accessors {
+    ($($ty:ty => $get:ident, $set:ident, $lua_get:literal, $lua_set:literal;)*) => {
+        $(
+            #[doc = concat!("Calls `Entity:", $lua_get, "(key)`.")]
+            pub fn $get(&self, state: LuaState, key: &str) -> lua::Result<$ty> {
+                self.call_method(state, $lua_get, |state| {
+                    lua::push(state, key);
+                })
+            }
+
+            #[doc = concat!("Calls `Entity:", $lua_set, "(key, value)`.")]
+            pub fn $set(&self, state: LuaState, key: &str, value: $ty) -> lua::Result<()> {
+                self.call_method(state, $lua_set, |state| {
+                    lua::push(state, key);
+                    lua::push(state, value);
+                })
+            }
+        )*
+    };
+}
+
+impl Entity {
+    /// Creates an [Entity] from the value at `stack_pos`, fails if the value is not a valid entity.
+    pub fn new(state: LuaState, stack_pos: i32) -> lua::Result<Self> {
+        lua::get(state, stack_pos)
+    }
+
+    /// The state the entity was taken from.
+    pub fn state(&self) -> crate::StateId {
+        self.0.state()
+    }
+
+    /// Calls the method `name` on this entity and returns the first result.
+    /// `args` should push the arguments, the entity itself is pushed automatically.
+    pub fn call_method<R, F>(&self, state: LuaState, name: &str, args: F) -> lua::Result<R>
+    where
+        R: FromStack,
+        F: FnOnce(LuaState),
+    {
+        let _guard = StackGuard::new(state);
+        lua::push(state, &self.0);
+        lua::get_field(state, -1, name)?;
+        lua::insert(state, -2);
+        let size_before_args = lua::top(state);
+        args(state);
+        let arg_count = lua::top(state) - size_before_args + 1;
+        lua::pcall_result(state, arg_count, 1)?;
+        lua::get(state, -1)
+    }
+
+    /// Calls `Entity:IsValid()`.
+    pub fn is_valid(&self, state: LuaState) -> bool {
+        self.call_method(state, "IsValid", |_| {}).unwrap_or(false)
+    }
+
+    /// Calls `Entity:EntIndex()`.
+    pub fn index(&self, state: LuaState) -> lua::Result<i32> {
+        self.call_method(state, "EntIndex", |_| {})
+    }
+
+    /// Calls `Entity:GetClass()`.
+    pub fn class(&self, state: LuaState) -> lua::Result<String> {
+        self.call_method(state, "GetClass", |_| {})
+    }
+
+    /// Calls `Entity:GetPos()`.
+    pub fn pos(&self, state: LuaState) -> lua::Result<Vector> {
+        self.call_method(state, "GetPos", |_| {})
+    }
+
+    /// Calls `Entity:SetPos(pos)`.
+    pub fn set_pos(&self, state: LuaState, pos: Vector) -> lua::Result<()> {
+        self.call_method(state, "SetPos", |state| {
+            lua::push(state, pos);
+        })
+    }
+
+    /// Calls `Entity:GetAngles()`.
+    pub fn angles(&self, state: LuaState) -> lua::Result<Angle> {
+        self.call_method(state, "GetAngles", |_| {})
+    }
+
+    /// Calls `Entity:SetAngles(angles)`.
+    pub fn set_angles(&self, state: LuaState, angles: Angle) -> lua::Result<()> {
+        self.call_method(state, "SetAngles", |state| {
+            lua::push(state, angles);
+        })
+    }
+
+    /// Calls `Entity:Health()`.
+    pub fn health(&self, state: LuaState) -> lua::Result<i32> {
+        self.call_method(state, "Health", |_| {})
+    }
+
+    /// Calls `Entity:GetModel()`, `None` if the entity has no model.
+    pub fn model(&self, state: LuaState) -> lua::Result<Option<String>> {
+        self.call_method(state, "GetModel", |_| {})
+    }
+
+    impl_nw_accessors! {
+        i32 => get_nw_int, set_nw_int, "GetNWInt", "SetNWInt";
+        f32 => get_nw_float, set_nw_float, "GetNWFloat", "SetNWFloat";
+        bool => get_nw_bool, set_nw_bool, "GetNWBool", "SetNWBool";
+        String => get_nw_string, set_nw_string, "GetNWString", "SetNWString";
+        Vector => get_nw_vector, set_nw_vector, "GetNWVector", "SetNWVector";
+        Angle => get_nw_angle, set_nw_angle, "GetNWAngle", "SetNWAngle";
+    }
+
+    /// Returns the entity as a [Player] if it is one.
+    pub fn into_player(self, state: LuaState) -> Option<Player> {
+        match self.call_method(state, "IsPlayer", |_| {}) {
+            Ok(true) => Some(Player(self)),
+            _ => None,
+        }
+    }
+}
+
+impl Player {
+    /// Calls `Player:SteamID64()`.
+    pub fn steam_id64(&self, state: LuaState) -> lua::Result<String> {
+        self.call_method(state, "SteamID64", |_| {})
+    }
+
+    /// Calls `Player:Nick()`.
+    pub fn nick(&self, state: LuaState) -> lua::Result<String> {
+        self.call_method(state, "Nick", |_| {})
+    }
+
+    /// Calls `Player:IsAdmin()`.
+    pub fn is_admin(&self, state: LuaState) -> lua::Result<bool> {
+        self.call_method(state, "IsAdmin", |_| {})
+    }
+
+    /// Calls `Player:ChatPrint(message)`, only available on the server.
+    pub fn chat_print(&self, state: LuaState, message: &str) -> lua::Result<()> {
+        self.call_method(state, "ChatPrint", |state| {
+            lua::push(state, message);
+        })
+    }
+
+    pub fn entity(&self) -> &Entity {
+        &self.0
+    }
+}
+
+impl Deref for Player {
+    type Target = Entity;
+    fn deref(&self) -> &Entity {
+        &self.0
+    }
+}
+
+impl From<Player> for Entity {
+    fn from(player: Player) -> Self {
+        player.0
+    }
+}
+
+impl FromStack for Entity {
+    fn from_stack(state: LuaState, stack_pos: i32) -> lua::Result<(Self, i32)> {
+        lua::expect_type(state, stack_pos, LuaType::Entity)?;
+        let entity = Entity(ArcRef::new(state, stack_pos));
+        if !entity.is_valid(state) {
+            return Err(lua::Error::CustomMessage("NULL entity".to_string()));
+        }
+        Ok((entity, 1))
+    }
+}
+
+impl FromStack for Player {
+    fn from_stack(state: LuaState, stack_pos: i32) -> lua::Result<(Self, i32)> {
+        let (entity, count) = Entity::from_stack(state, stack_pos)?;
+        match entity.into_player(state) {
+            Some(player) => Ok((player, count)),
+            None => Err(lua::Error::CustomMessage(
+                "Player expected, got Entity".to_string(),
+            )),
+        }
+    }
+}
+
+impl ToStack for Entity {
+    fn push(self, state: LuaState) -> i32 {
+        (&self).push(state)
+    }
+}
+
+impl ToStack for &Entity {
+    fn push(self, state: LuaState) -> i32 {
+        (&self.0).push(state)
+    }
+}
+
+impl ToStack for Player {
+    fn push(self, state: LuaState) -> i32 {
+        self.0.push(state)
+    }
+}
+
+impl ToStack for &Player {
+    fn push(self, state: LuaState) -> i32 {
+        (&self.0).push(state)
+    }
+}
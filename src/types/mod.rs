@@ -0,0 +1,8 @@
+//! Rust representations of garry's mod types.
+mod color;
+mod entity;
+mod vector;
+
+pub use color::Color;
+pub use entity::{Entity, Player};
+pub use vector::{Angle, Vector};
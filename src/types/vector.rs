@@ -0,0 +1,293 @@
+use crate::lua::{self, FromStack, LuaState, LuaType, ToStack};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// Gmod's `Vector`.
+///
+///```
+///# use gmrs::prelude::*;
+/// let forward = Vector::new(1.0, 0.0, 0.0);
+/// let up = Vector::new(0.0, 0.0, 1.0);
+/// assert_eq!(forward.cross(up), Vector::new(0.0, -1.0, 0.0));
+/// assert_eq!((forward * 2.0 + up).length_sqr(), 5.0);
+///```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Gmod's `Angle`, all values are in degrees.
+///
+///```
+///# use gmrs::prelude::*;
+/// let angle = Angle::new(0.0, 90.0, 0.0);
+/// let forward = angle.forward();
+/// assert!((forward - Vector::new(0.0, 1.0, 0.0)).length() < 1e-6);
+/// assert!((forward.angle().yaw - 90.0).abs() < 1e-4);
+///```
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Angle {
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32,
+}
+
+impl Vector {
+    pub const ZERO: Vector = Vector::new(0.0, 0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Vector) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Vector) -> Vector {
+        Vector::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.length_sqr().sqrt()
+    }
+
+    pub fn length_sqr(self) -> f32 {
+        self.dot(self)
+    }
+
+    /// Length of the vector ignoring the z component.
+    pub fn length_2d(self) -> f32 {
+        self.length_2d_sqr().sqrt()
+    }
+
+    pub fn length_2d_sqr(self) -> f32 {
+        self.x * self.x + self.y * self.y
+    }
+
+    pub fn distance(self, other: Vector) -> f32 {
+        (self - other).length()
+    }
+
+    pub fn distance_sqr(self, other: Vector) -> f32 {
+        (self - other).length_sqr()
+    }
+
+    /// Returns the vector with length 1, like `Vector:GetNormalized`.
+    /// The zero vector stays zero.
+    pub fn normalized(self) -> Vector {
+        self / (self.length() + f32::EPSILON)
+    }
+
+    /// Normalizes the vector in place, like `Vector:Normalize`.
+    pub fn normalize(&mut self) {
+        *self = self.normalized()
+    }
+
+    pub fn is_zero(self) -> bool {
+        self == Vector::ZERO
+    }
+
+    /// Linear interpolation between `self` and `to`, like `LerpVector(fraction, self, to)`.
+    pub fn lerp(self, to: Vector, fraction: f32) -> Vector {
+        self + (to - self) * fraction
+    }
+
+    /// Returns the angle pointing in the direction of this vector, like `Vector:Angle`.
+    pub fn angle(self) -> Angle {
+        if self.x == 0.0 && self.y == 0.0 {
+            let pitch = if self.z > 0.0 { 270.0 } else { 90.0 };
+            return Angle::new(pitch, 0.0, 0.0);
+        }
+        let mut yaw = self.y.atan2(self.x).to_degrees();
+        if yaw < 0.0 {
+            yaw += 360.0;
+        }
+        let mut pitch = (-self.z).atan2(self.length_2d()).to_degrees();
+        if pitch < 0.0 {
+            pitch += 360.0;
+        }
+        Angle::new(pitch, yaw, 0.0)
+    }
+}
+
+impl Angle {
+    pub const ZERO: Angle = Angle::new(0.0, 0.0, 0.0);
+
+    pub const fn new(pitch: f32, yaw: f32, roll: f32) -> Self {
+        Self { pitch, yaw, roll }
+    }
+
+    /// Returns the forward, right and up direction vectors.
+    fn vectors(self) -> (Vector, Vector, Vector) {
+        let (sp, cp) = self.pitch.to_radians().sin_cos();
+        let (sy, cy) = self.yaw.to_radians().sin_cos();
+        let (sr, cr) = self.roll.to_radians().sin_cos();
+        let forward = Vector::new(cp * cy, cp * sy, -sp);
+        let right = Vector::new(-sr * sp * cy + cr * sy, -sr * sp * sy - cr * cy, -sr * cp);
+        let up = Vector::new(cr * sp * cy + sr * sy, cr * sp * sy - sr * cy, cr * cp);
+        (forward, right, up)
+    }
+
+    /// Direction vector this angle points to, like `Angle:Forward`.
+    pub fn forward(self) -> Vector {
+        self.vectors().0
+    }
+
+    /// Like `Angle:Right`.
+    pub fn right(self) -> Vector {
+        self.vectors().1
+    }
+
+    /// Like `Angle:Up`.
+    pub fn up(self) -> Vector {
+        self.vectors().2
+    }
+
+    /// Returns the angle with every component in the range [-180, 180], like `Angle:Normalize`.
+    pub fn normalized(self) -> Angle {
+        fn normalize(angle: f32) -> f32 {
+            let angle = angle % 360.0;
+            if angle > 180.0 {
+                angle - 360.0
+            } else if angle < -180.0 {
+                angle + 360.0
+            } else {
+                angle
+            }
+        }
+        Angle::new(
+            normalize(self.pitch),
+            normalize(self.yaw),
+            normalize(self.roll),
+        )
+    }
+
+    /// Normalizes the angle in place.
+    pub fn normalize(&mut self) {
+        *self = self.normalized()
+    }
+
+    pub fn is_zero(self) -> bool {
+        self == Angle::ZERO
+    }
+
+    /// Linear interpolation between `self` and `to`, like `LerpAngle(fraction, self, to)`.
+    pub fn lerp(self, to: Angle, fraction: f32) -> Angle {
+        self + (to - self) * fraction
+    }
+}
+
+macro_rules! impl_vector_ops {
+    ($ty:ident, $x:ident, $y:ident, $z:ident) => {
+        impl Add for $ty {
+            type Output = $ty;
+            fn add(self, rhs: $ty) -> $ty {
+                $ty::new(self.$x + rhs.$x, self.$y + rhs.$y, self.$z + rhs.$z)
+            }
+        }
+        impl Sub for $ty {
+            type Output = $ty;
+            fn sub(self, rhs: $ty) -> $ty {
+                $ty::new(self.$x - rhs.$x, self.$y - rhs.$y, self.$z - rhs.$z)
+            }
+        }
+        impl Mul<f32> for $ty {
+            type Output = $ty;
+            fn mul(self, rhs: f32) -> $ty {
+                $ty::new(self.$x * rhs, self.$y * rhs, self.$z * rhs)
+            }
+        }
+        impl Mul<$ty> for f32 {
+            type Output = $ty;
+            fn mul(self, rhs: $ty) -> $ty {
+                rhs * self
+            }
+        }
+        impl Div<f32> for $ty {
+            type Output = $ty;
+            fn div(self, rhs: f32) -> $ty {
+                $ty::new(self.$x / rhs, self.$y / rhs, self.$z / rhs)
+            }
+        }
+        impl Neg for $ty {
+            type Output = $ty;
+            fn neg(self) -> $ty {
+                $ty::new(-self.$x, -self.$y, -self.$z)
+            }
+        }
+        impl AddAssign for $ty {
+            fn add_assign(&mut self, rhs: $ty) {
+                *self = *self + rhs
+            }
+        }
+        impl SubAssign for $ty {
+            fn sub_assign(&mut self, rhs: $ty) {
+                *self = *self - rhs
+            }
+        }
+        impl MulAssign<f32> for $ty {
+            fn mul_assign(&mut self, rhs: f32) {
+                *self = *self * rhs
+            }
+        }
+        impl DivAssign<f32> for $ty {
+            fn div_assign(&mut self, rhs: f32) {
+                *self = *self / rhs
+            }
+        }
+        impl From<[f32; 3]> for $ty {
+            fn from(v: [f32; 3]) -> Self {
+                $ty::new(v[0], v[1], v[2])
+            }
+        }
+        impl From<$ty> for [f32; 3] {
+            fn from(v: $ty) -> Self {
+                [v.$x, v.$y, v.$z]
+            }
+        }
+    };
+}
+
+impl_vector_ops!(Vector, x, y, z);
+impl_vector_ops!(Angle, pitch, yaw, roll);
+
+/// Component wise multiplication, like gmod's `Vector * Vector`.
+impl Mul for Vector {
+    type Output = Vector;
+    fn mul(self, rhs: Vector) -> Vector {
+        Vector::new(self.x * rhs.x, self.y * rhs.y, self.z * rhs.z)
+    }
+}
+
+impl ToStack for Vector {
+    fn push(self, state: LuaState) -> i32 {
+        lua::push_vector(state, self.into());
+        1
+    }
+}
+
+impl FromStack for Vector {
+    fn from_stack(state: LuaState, stack_pos: i32) -> lua::Result<(Self, i32)> {
+        lua::expect_type(state, stack_pos, LuaType::Vector)?;
+        Ok((lua::get_vector(state, stack_pos).into(), 1))
+    }
+}
+
+impl ToStack for Angle {
+    fn push(self, state: LuaState) -> i32 {
+        lua::push_angle(state, self.into());
+        1
+    }
+}
+
+impl FromStack for Angle {
+    fn from_stack(state: LuaState, stack_pos: i32) -> lua::Result<(Self, i32)> {
+        lua::expect_type(state, stack_pos, LuaType::Angle)?;
+        Ok((lua::get_angle(state, stack_pos).into(), 1))
+    }
+}
@@ -0,0 +1,108 @@
+use gmrs::{concommand, prelude::*};
+
+#[gmrs::entry]
+fn open(state: LuaState) {
+    let result = concommand::add(
+        state,
+        "test_echo",
+        |state, cmd| {
+            let caller = match &cmd.player {
+                Some(ply) => ply.nick(state)?,
+                None => "console".to_string(),
+            };
+            gmrs::print!(
+                state,
+                "{} ran {} with {:?} '{}'",
+                caller,
+                cmd.name,
+                cmd.args,
+                cmd.arg_str
+            );
+            Ok(())
+        },
+        "Echoes the arguments",
+        gmrs::fcvar::NONE,
+    );
+    result.unwrap();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn commands_receive_the_caller_and_arguments() {
+    let server = gmrs::test_server!();
+    server
+        .run("RunConsoleCommand('test_echo', 'a', 2)")
+        .unwrap();
+    server.assert_output_contains("console ran test_echo with [\"a\", \"2\"] 'a 2'");
+    server
+        .run("concommand.Run(player.CreateNextBot('bot'), 'test_echo', {}, '')")
+        .unwrap();
+    server.assert_output_contains("bot ran test_echo with [] ''");
+}
+
+#[test]
+fn errors_are_raised_in_lua() {
+    let server = gmrs::test_server!();
+    concommand::add(
+        server.state(),
+        "test_fail",
+        |_, _| Err("command failed".into()),
+        "",
+        gmrs::fcvar::NONE,
+    )
+    .unwrap();
+    let (ok, message): (bool, String) = server
+        .eval("pcall(RunConsoleCommand, 'test_fail')")
+        .unwrap();
+    assert!(!ok);
+    assert_eq!(message, "command failed");
+}
+
+#[test]
+fn autocomplete_returns_suggestions() {
+    let server = gmrs::test_server!();
+    concommand::add_with_autocomplete(
+        server.state(),
+        "test_complete",
+        |_, _| Ok(()),
+        |name, arg_str| {
+            vec![
+                format!("{} {}1", name, arg_str),
+                format!("{} {}2", name, arg_str),
+            ]
+        },
+        "",
+        gmrs::fcvar::NONE,
+    )
+    .unwrap();
+    server
+        .run("local _, completions = concommand.GetTable() result = completions.test_complete('test_complete', 'ab')")
+        .unwrap();
+    assert_eq!(
+        server.eval::<String>("table.concat(result, ',')").unwrap(),
+        "test_complete ab1,test_complete ab2"
+    );
+}
+
+#[test]
+fn commands_are_removed() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    concommand::add(state, "test_removed", |_, _| Ok(()), "", gmrs::fcvar::NONE).unwrap();
+    concommand::remove(state, "test_removed").unwrap();
+    assert!(server
+        .eval::<Option<OwnedRef>>("concommand.GetTable().test_removed")
+        .unwrap()
+        .is_none());
+    assert!(server
+        .eval::<Option<OwnedRef>>("concommand.GetTable().test_echo")
+        .unwrap()
+        .is_some());
+    concommand::remove_all(state);
+    assert!(server
+        .eval::<Option<OwnedRef>>("concommand.GetTable().test_echo")
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,62 @@
+use gmrs::{console, prelude::*, HudPrint};
+use std::time::Duration;
+
+#[gmrs::entry]
+fn open(_state: LuaState) {}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+// a single test, output sent from other threads goes to the first state that opened the module
+#[test]
+fn output_from_other_threads_is_printed_on_think() {
+    let server = gmrs::test_server!();
+
+    // nothing is queued on the lua thread, so it is printed right away
+    console::print("from the lua thread");
+    server.assert_output_contains("from the lua thread\n");
+
+    std::thread::spawn(|| {
+        console::print("first");
+        console::msg("second ");
+        console::msgc(&[(Color::RED, "third"), (Color::WHITE, "\n")]);
+        console::error_no_halt("fourth\n");
+    })
+    .join()
+    .unwrap();
+    assert!(!server.output().contains("first"));
+    server.think().unwrap();
+    server.assert_output_contains("first\nsecond third\nfourth\n");
+
+    let ply: Player = server.eval("player.CreateNextBot('bot')").unwrap();
+    let target = ply.clone();
+    std::thread::spawn(move || console::print_to(&target, HudPrint::Talk, "hello"))
+        .join()
+        .unwrap();
+    assert!(server.think_until(Duration::from_secs(5), |server| {
+        server
+            .eval::<usize>("#player.GetAll()[1]:GetPrintedMessages()")
+            .unwrap()
+            == 1
+    }));
+    assert_eq!(
+        server
+            .eval::<(i32, String)>(
+                "player.GetAll()[1]:GetPrintedMessages()[1].kind, player.GetAll()[1]:GetPrintedMessages()[1].message"
+            )
+            .unwrap(),
+        (HudPrint::Talk as i32, "hello".to_string())
+    );
+    drop(ply);
+
+    // lines sent while a flush is queued are printed by the same flush
+    server.take_output();
+    let worker = std::thread::spawn(|| {
+        for i in 0..3 {
+            console::print(&format!("line {}", i));
+        }
+    });
+    worker.join().unwrap();
+    server.think().unwrap();
+    assert_eq!(server.take_output(), "line 0\nline 1\nline 2\n");
+}
@@ -0,0 +1,88 @@
+use gmrs::{
+    convar::{self, ConVar},
+    prelude::*,
+};
+use std::sync::{Arc, Mutex};
+
+#[gmrs::entry]
+fn open(_state: LuaState) {}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn values_are_cached_and_updated() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let interval: ConVar<f64> =
+        convar::create(state, "test_interval", 5.0, gmrs::fcvar::ARCHIVE, "").unwrap();
+    assert_eq!(interval.get(), 5.0);
+    server
+        .run("RunConsoleCommand('test_interval', '2.5')")
+        .unwrap();
+    assert_eq!(interval.get(), 2.5);
+    interval.set(state, 7.0).unwrap();
+    assert_eq!(interval.get(), 7.0);
+    assert_eq!(
+        server
+            .eval::<String>("GetConVar('test_interval'):GetString()")
+            .unwrap(),
+        "7"
+    );
+}
+
+#[test]
+fn existing_convars_keep_their_value() {
+    let server = gmrs::test_server!();
+    server.run("CreateConVar('test_existing', 'yes')").unwrap();
+    let existing: ConVar<String> = convar::create(
+        server.state(),
+        "test_existing",
+        "no".to_string(),
+        gmrs::fcvar::NONE,
+        "",
+    )
+    .unwrap();
+    assert_eq!(existing.get(), "yes");
+    let number: ConVar<i32> =
+        convar::create(server.state(), "test_existing", 0, gmrs::fcvar::NONE, "").unwrap();
+    // invalid values become zero like `ConVar:GetInt`
+    assert_eq!(number.get(), 0);
+}
+
+#[test]
+fn change_callbacks_get_the_old_and_new_values() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let enabled: ConVar<bool> =
+        convar::create(state, "test_enabled", false, gmrs::fcvar::NONE, "").unwrap();
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let recorded = changes.clone();
+    enabled.on_change(move |_, old, new| recorded.lock().unwrap().push((*old, *new)));
+    // callbacks can set the convar again without deadlocking
+    let reset = enabled.clone();
+    enabled.on_change(move |state, _, new| {
+        if *new {
+            reset.set(state, false).unwrap();
+        }
+    });
+    server
+        .run("GetConVar('test_enabled'):SetBool(true)")
+        .unwrap();
+    assert_eq!(*changes.lock().unwrap(), [(false, true), (true, false)]);
+    assert!(!enabled.get());
+}
+
+#[test]
+fn callbacks_are_removed_with_remove_all() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let value: ConVar<i32> =
+        convar::create(state, "test_removed", 1, gmrs::fcvar::NONE, "").unwrap();
+    convar::remove_all(state);
+    assert!(server
+        .eval::<bool>("next(cvars.GetConVarCallbacks('test_removed')) == nil")
+        .unwrap());
+    server.run("RunConsoleCommand('test_removed', 5)").unwrap();
+    assert_eq!(value.get(), 1);
+}
@@ -0,0 +1,118 @@
+use gmrs::{lua::ResultExt, prelude::*};
+
+#[gmrs::function]
+fn fail_with_table(state: LuaState, code: i32) -> lua::Result<()> {
+    lua::create_table(state).set(state, "code", code);
+    Err(lua::Error::from_stack_value(state, -1))
+}
+
+#[gmrs::function]
+fn fail_with_message(message: String) -> lua::Result<()> {
+    Err(message.into())
+}
+
+#[gmrs::function]
+fn rethrow(state: LuaState, func: OwnedRef) -> lua::Result<()> {
+    lua::push(state, &func);
+    lua::pcall_result(state, 0, 0)
+}
+
+#[gmrs::function]
+fn fail_with_context(state: LuaState, code: i32) -> lua::Result<()> {
+    let error = lua::create_table(state);
+    error.set(state, "code", code);
+    lua::error_value(state, error).context("while failing")
+}
+
+#[gmrs::entry]
+fn open(state: LuaState) {
+    gmrs::set_global(state, "fail_with_table", NativeFunc::new(fail_with_table)).unwrap();
+    gmrs::set_global(
+        state,
+        "fail_with_message",
+        NativeFunc::new(fail_with_message),
+    )
+    .unwrap();
+    gmrs::set_global(state, "rethrow", NativeFunc::new(rethrow)).unwrap();
+    gmrs::set_global(
+        state,
+        "fail_with_context",
+        NativeFunc::new(fail_with_context),
+    )
+    .unwrap();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn error_values_reach_lua_unchanged() {
+    let server = gmrs::test_server!();
+    server.run("ok, err = pcall(fail_with_table, 42)").unwrap();
+    server.assert_global("ok", false);
+    assert_eq!(server.eval::<i32>("err.code").unwrap(), 42);
+    server
+        .run("ok, err = pcall(fail_with_message, 'broken')")
+        .unwrap();
+    server.assert_global("err", "broken".to_string());
+}
+
+#[test]
+fn error_values_survive_a_round_trip_through_rust() {
+    let server = gmrs::test_server!();
+    server
+        .run("ok, err = pcall(rethrow, function() error({code = 7}) end)")
+        .unwrap();
+    server.assert_global("ok", false);
+    assert_eq!(server.eval::<i32>("err.code").unwrap(), 7);
+}
+
+#[test]
+fn context_keeps_the_error_value() {
+    let server = gmrs::test_server!();
+    server.run("ok, err = pcall(fail_with_context, 3)").unwrap();
+    assert_eq!(server.eval::<i32>("err.code").unwrap(), 3);
+}
+
+#[test]
+fn error_values_are_displayed_with_tostring() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    server
+        .run("described = setmetatable({}, {__tostring = function() return 'not found' end})")
+        .unwrap();
+    let described: OwnedRef = server.global("described").unwrap();
+    let error = lua::Error::value(state, &described);
+    assert_eq!(error.to_string(), "not found");
+    let error = Err::<(), _>(error).context("loading").unwrap_err();
+    assert_eq!(error.to_string(), "loading: not found");
+    assert!(error.as_value().is_some());
+
+    let error = server.run("error({})").unwrap_err();
+    assert!(error.to_string().starts_with("table: "), "{}", error);
+}
+
+#[derive(Debug)]
+struct NotSend(std::rc::Rc<String>);
+impl std::fmt::Display for NotSend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not send: {}", self.0)
+    }
+}
+impl std::error::Error for NotSend {}
+
+fn not_send() -> lua::Result<()> {
+    Err(NotSend(std::rc::Rc::new("io".to_string())))?;
+    Ok(())
+}
+
+fn boxed() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    Err("boxed".into())
+}
+
+#[test]
+fn errors_that_are_not_send_can_be_converted() {
+    assert_eq!(not_send().unwrap_err().to_string(), "not send: io");
+    let error = boxed().map_err(lua::Error::from_boxed).unwrap_err();
+    assert_eq!(error.to_string(), "boxed");
+}
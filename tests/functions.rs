@@ -0,0 +1,52 @@
+use gmrs::prelude::*;
+
+#[gmrs::function]
+fn add(a: f64, b: f64) -> lua::Result<f64> {
+    Ok(a + b)
+}
+
+#[gmrs::function(name = "greet")]
+fn greet_player(name: String, times: Option<u32>) -> lua::Result<String> {
+    Ok(name.repeat(times.unwrap_or(1) as usize))
+}
+
+#[gmrs::entry]
+fn open(state: LuaState) {
+    gmrs::set_global(state, "add", NativeFunc::new(add)).unwrap();
+    gmrs::set_global(state, "greet", NativeFunc::new(greet_player)).unwrap();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+/// Calls `code` with pcall and returns the error message.
+fn error_of(server: &gmrs::testing::TestServer, code: &str) -> String {
+    let (ok, message) = server
+        .eval::<(bool, String)>(&format!("pcall(function() {} end)", code))
+        .unwrap();
+    assert!(!ok, "`{}` didnt fail", code);
+    message
+}
+
+#[test]
+fn converts_arguments_and_results() {
+    let server = gmrs::test_server!();
+    assert_eq!(server.eval::<f64>("add(1, 2.5)").unwrap(), 3.5);
+    assert_eq!(server.eval::<String>("greet('ab', 3)").unwrap(), "ababab");
+    assert_eq!(server.eval::<String>("greet('ab')").unwrap(), "ab");
+}
+
+#[test]
+fn bad_arguments_are_reported_like_lua() {
+    let server = gmrs::test_server!();
+    assert_eq!(
+        error_of(&server, "add(1, 'x')"),
+        "bad argument #2 to 'add' (number expected, got string)"
+    );
+    assert_eq!(
+        error_of(&server, "add()"),
+        "bad argument #1 to 'add' (number expected, got no value)"
+    );
+    // the lua name is used, not the rust one
+    assert!(error_of(&server, "greet({})").starts_with("bad argument #1 to 'greet'"));
+}
@@ -0,0 +1,80 @@
+use gmrs::prelude::*;
+
+#[gmrs::entry]
+fn open(_state: LuaState) {}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn values_are_read_and_written_by_path() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    gmrs::set_global_path(state, "test_module.config.name", "server").unwrap();
+    assert_eq!(
+        server.eval::<String>("test_module.config.name").unwrap(),
+        "server"
+    );
+    assert_eq!(
+        gmrs::get_global::<String>(state, "test_module.config.name").unwrap(),
+        "server"
+    );
+    assert_eq!(
+        gmrs::get_global::<Option<f64>>(state, "test_module.config.missing").unwrap(),
+        None
+    );
+}
+
+#[test]
+fn invalid_paths_name_the_failing_segment() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    server.run("test_value = 1").unwrap();
+    assert_eq!(
+        gmrs::get_global::<f64>(state, "test_value.field.other")
+            .unwrap_err()
+            .to_string(),
+        "failed to resolve 'test_value.field.other', 'test_value' is number"
+    );
+    assert_eq!(
+        gmrs::call_global::<_, ()>(state, "string.missing", ())
+            .unwrap_err()
+            .to_string(),
+        "failed to resolve 'string.missing', 'string.missing' is nil"
+    );
+    assert_eq!(
+        gmrs::call_global::<_, ()>(state, "string.missing.inner", ())
+            .unwrap_err()
+            .to_string(),
+        "failed to resolve 'string.missing.inner', 'string.missing' is nil"
+    );
+    assert!(gmrs::set_global_path(state, "test_value..other", 1).is_err());
+    assert!(gmrs::set_global(state, "test\0value", 1).is_err());
+}
+
+#[test]
+fn functions_are_called_and_cached() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let upper: String = gmrs::call_global(state, "string.upper", "abc").unwrap();
+    assert_eq!(upper, "ABC");
+    let (a, b): (f64, f64) = gmrs::call_global(state, "math.modf", 2.5).unwrap();
+    assert_eq!((a, b), (2.0, 0.5));
+
+    server.run("function test_version() return 1 end").unwrap();
+    assert_eq!(
+        gmrs::call_global::<_, f64>(state, "test_version", ()).unwrap(),
+        1.0
+    );
+    server.run("function test_version() return 2 end").unwrap();
+    // the cached function keeps being used until the cache is cleared
+    assert_eq!(
+        gmrs::call_global::<_, f64>(state, "test_version", ()).unwrap(),
+        1.0
+    );
+    gmrs::globals::clear_cache(state);
+    assert_eq!(
+        gmrs::call_global::<_, f64>(state, "test_version", ()).unwrap(),
+        2.0
+    );
+}
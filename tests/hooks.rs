@@ -0,0 +1,99 @@
+use gmrs::{hooks, prelude::*};
+
+#[gmrs::entry]
+fn open(state: LuaState) {
+    let said = |state: LuaState, ply: Player, text: String| {
+        if text == "!ping" {
+            let _ = ply.chat_print(state, "pong");
+            return Some(String::new());
+        }
+        None
+    };
+    hooks::add(state, "PlayerSay", "test_ping", said)
+        .unwrap()
+        .detach();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn callbacks_receive_arguments_and_return_results() {
+    let server = gmrs::test_server!();
+    server
+        .run(
+            "ply = player.CreateNextBot('bot') result = hook.Run('PlayerSay', ply, '!ping', false)",
+        )
+        .unwrap();
+    server.assert_global("result", String::new());
+    assert_eq!(
+        server
+            .eval::<String>("ply:GetPrintedMessages()[1].message")
+            .unwrap(),
+        "pong"
+    );
+    // returning None lets the other hooks run
+    server
+        .run("other = hook.Run('PlayerSay', ply, 'hello', false)")
+        .unwrap();
+    server.assert_global::<Option<String>>("other", None);
+}
+
+#[test]
+fn dropping_the_handle_removes_the_hook() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let handle = hooks::add(state, "TestDrop", "counter", |value: f64| value + 1.0).unwrap();
+    assert_eq!(
+        hooks::run::<Option<f64>, _>(state, "TestDrop", 1.0).unwrap(),
+        Some(2.0)
+    );
+    drop(handle);
+    assert_eq!(
+        hooks::run::<Option<f64>, _>(state, "TestDrop", 1.0).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn replaced_hooks_arent_removed_by_the_old_handle() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let first = hooks::add(state, "TestReplace", "id", || 1.0).unwrap();
+    let second = hooks::add(state, "TestReplace", "id", || 2.0).unwrap();
+    drop(first);
+    assert_eq!(
+        hooks::run::<Option<f64>, _>(state, "TestReplace", ()).unwrap(),
+        Some(2.0)
+    );
+    drop(second);
+    assert_eq!(
+        hooks::run::<Option<f64>, _>(state, "TestReplace", ()).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn remove_all_removes_detached_hooks() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let kept = hooks::add(state, "TestRemoveAll", "kept", || true).unwrap();
+    hooks::add(state, "TestRemoveAll", "detached", || true)
+        .unwrap()
+        .detach();
+    hooks::remove_all(state);
+    assert_eq!(
+        hooks::run::<Option<bool>, _>(state, "TestRemoveAll", ()).unwrap(),
+        None
+    );
+    assert_eq!(
+        server
+            .eval::<usize>(
+                "select('#', hook.Run('PlayerSay', player.CreateNextBot('bot'), '!ping'))"
+            )
+            .unwrap(),
+        0
+    );
+    // the handle of a removed hook does nothing
+    drop(kept);
+}
@@ -0,0 +1,77 @@
+use gmrs::logging::{self, Level};
+use std::{fs, path::PathBuf};
+
+/// A new empty directory for the log files of a test.
+fn log_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("gmrs-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn read_lines(directory: &PathBuf, file: &str) -> Vec<String> {
+    fs::read_to_string(directory.join(file))
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Checks that `line` starts with a `HH:MM:SS.mmm` timestamp and returns the rest.
+fn strip_timestamp(line: &str) -> &str {
+    let (timestamp, rest) = line.split_at(13);
+    let digits: Vec<_> = timestamp.trim_end().split([':', '.']).collect();
+    assert_eq!(digits.len(), 4, "bad timestamp in '{}'", line);
+    for (digit, max) in digits.iter().zip([24, 60, 60, 1000]) {
+        assert!(
+            digit.parse::<u32>().unwrap() < max,
+            "bad timestamp in '{}'",
+            line
+        );
+    }
+    rest
+}
+
+#[test]
+fn file_names_cant_leave_the_directory() {
+    let directory = log_directory("names");
+    for name in ["../escape", "nested/file", "nested\\file", ".."] {
+        let result = logging::builder().directory(&directory).file(name).init();
+        assert!(result.is_err(), "'{}' was accepted", name);
+    }
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 0);
+}
+
+// the logger is global so everything that needs it is checked by a single test
+#[test]
+fn records_are_written_and_rotated() {
+    let directory = log_directory("rotate");
+    logging::builder()
+        .level(Level::Debug)
+        .directory(&directory)
+        .file("server")
+        .max_file_size(1)
+        .max_files(2)
+        .init()
+        .unwrap();
+
+    for index in 1..=4 {
+        log::debug!(target: "tests", "record {}", index);
+    }
+    tracing::warn!(target: "tests", count = 3, name = "bob", "record {}", 5);
+    log::trace!(target: "tests", "ignored");
+
+    // every record is bigger than the max size so each one ends up in its own file,
+    // `server.1.log` and `server.2.log` are kept and records 1 and 2 are dropped
+    let lines = read_lines(&directory, "server.log");
+    assert_eq!(lines.len(), 1);
+    assert_eq!(
+        strip_timestamp(&lines[0]),
+        "WARN  tests: record 5 count=3 name=\"bob\""
+    );
+    let lines = read_lines(&directory, "server.1.log");
+    assert_eq!(strip_timestamp(&lines[0]), "DEBUG tests: record 4");
+    let lines = read_lines(&directory, "server.2.log");
+    assert_eq!(strip_timestamp(&lines[0]), "DEBUG tests: record 3");
+    assert!(!directory.join("server.3.log").exists());
+}
@@ -0,0 +1,4 @@
+local module = ...
+
+module.loaded_before_error = true
+error("broken on purpose")
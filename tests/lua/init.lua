@@ -0,0 +1,5 @@
+local module = ...
+
+function module.shout(name)
+    return module.greeting(name):upper()
+end
@@ -0,0 +1,90 @@
+/// Arithmetic helpers.
+#[gmrs::module(name = "test_arithmetic", version = "1.2.3")]
+mod arithmetic {
+    use gmrs::prelude::*;
+
+    /// Half a turn.
+    #[constant]
+    const PI: f64 = std::f64::consts::PI;
+
+    /// Adds two numbers.
+    #[function]
+    fn add(a: f64, b: f64) -> lua::Result<f64> {
+        Ok(a + b)
+    }
+
+    #[function(name = "sub")]
+    fn subtract(a: f64, b: Option<f64>) -> lua::Result<f64> {
+        Ok(a - b.unwrap_or(0.0))
+    }
+
+    // not a marker, only bare attributes register functions
+    #[gmrs::function]
+    fn hidden() -> lua::Result<()> {
+        Ok(())
+    }
+
+    pub struct Counter(f64);
+    impl UserType for Counter {
+        fn build_metatable(builder: &mut MetatableBuilder<Self>) {
+            builder.method("increment", counter_increment);
+        }
+    }
+
+    #[gmrs::function(name = "increment")]
+    fn counter_increment(this: UserData<Counter>) -> lua::Result<f64> {
+        Ok(this.with(|counter| {
+            counter.0 += 1.0;
+            counter.0
+        }))
+    }
+
+    #[constructor]
+    fn new_counter(start: f64) -> lua::Result<UserData<Counter>> {
+        Ok(UserData::new(Counter(start)))
+    }
+
+    #[entry]
+    fn init(state: LuaState) {
+        gmrs::print!(state, "test_arithmetic loaded");
+    }
+}
+
+#[gmrs::exit]
+fn close(_state: gmrs::lua::LuaState) {}
+
+#[test]
+fn items_are_registered_in_the_module_table() {
+    let server = gmrs::test_server!();
+    server.assert_output_contains("test_arithmetic loaded");
+    assert_eq!(
+        server.eval::<f64>("test_arithmetic.add(1, 2)").unwrap(),
+        3.0
+    );
+    assert_eq!(server.eval::<f64>("test_arithmetic.sub(5)").unwrap(), 5.0);
+    assert_eq!(
+        server.eval::<f64>("test_arithmetic.PI").unwrap(),
+        std::f64::consts::PI
+    );
+    assert_eq!(
+        server.eval::<String>("test_arithmetic._VERSION").unwrap(),
+        "1.2.3"
+    );
+    server
+        .run("counter = test_arithmetic.Counter(10) counter:increment()")
+        .unwrap();
+    assert_eq!(server.eval::<f64>("counter:increment()").unwrap(), 12.0);
+    assert!(server
+        .eval::<Option<gmrs::OwnedRef>>("test_arithmetic.hidden")
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        server
+            .eval::<(bool, String)>("pcall(test_arithmetic.sub, 'x')")
+            .unwrap(),
+        (
+            false,
+            "bad argument #1 to 'sub' (number expected, got string)".to_string()
+        )
+    );
+}
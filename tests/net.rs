@@ -0,0 +1,121 @@
+use gmrs::{
+    net::{self, NetMessage},
+    prelude::*,
+};
+
+#[derive(Debug, NetMessage)]
+#[net(name = "test_hit")]
+struct Hit {
+    target: Entity,
+    #[net(bits = 10)]
+    damage: u32,
+    pos: Vector,
+    weapon: Option<String>,
+    tags: Vec<String>,
+    #[net(skip)]
+    local_only: bool,
+}
+
+#[gmrs::entry]
+fn open(state: LuaState) {
+    net::add_network_string(state, Hit::NAME).unwrap();
+    net::add_network_string(state, "test_raw").unwrap();
+    let result = net::receive_message(state, |state, hit: Hit, ply| {
+        let sender = match ply {
+            Some(ply) => ply.nick(state)?,
+            None => "server".to_string(),
+        };
+        gmrs::print!(
+            state,
+            "{} hit for {} with {:?} {:?} {}",
+            sender,
+            hit.damage,
+            hit.weapon,
+            hit.tags,
+            hit.local_only
+        );
+        Ok(())
+    });
+    result.unwrap();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn derived_messages_round_trip() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let target: Entity = server.eval("ents.Create('prop_physics')").unwrap();
+    let hit = Hit {
+        target,
+        damage: 700,
+        pos: Vector::new(1.0, 2.0, 3.0),
+        weapon: Some("crowbar".to_string()),
+        tags: vec!["head".to_string(), "crit".to_string()],
+        local_only: true,
+    };
+    hit.start(state).unwrap().broadcast().unwrap();
+    server
+        .run("sent = net.TakeSent() net.Deliver(sent[1], player.CreateNextBot('bot'))")
+        .unwrap();
+    assert_eq!(
+        server.eval::<String>("sent[1].target").unwrap(),
+        "broadcast"
+    );
+    server.assert_output_contains(
+        "bot hit for 700 with Some(\"crowbar\") [\"head\", \"crit\"] false",
+    );
+}
+
+#[test]
+fn values_are_written_with_the_given_bits() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let mut writer = net::start(state, "test_raw", false).unwrap();
+    writer.write_uint(1025, 10).unwrap();
+    writer.write_int(-3, 4).unwrap();
+    writer.write_bool(true).unwrap();
+    writer.write_string("text").unwrap();
+    writer.write(&u64::MAX).unwrap();
+    assert_eq!(writer.bytes_written().unwrap(), 15);
+    let ply: Player = server.eval("player.CreateNextBot('bot')").unwrap();
+    writer.send(&ply).unwrap();
+
+    net::receive(state, "test_raw", |state, reader, _| {
+        let values = (
+            reader.read_uint(10)?,
+            reader.read_int(4)?,
+            reader.read_bool()?,
+            reader.read_string()?,
+            reader.read::<u64>()?,
+        );
+        gmrs::print!(state, "{:?} {}", values, reader.bytes_left()?);
+        Ok(())
+    })
+    .unwrap();
+    server.run("net.Deliver(net.TakeSent()[1])").unwrap();
+    server.assert_output_contains(&format!("(1, -3, true, \"text\", {}) 0", u64::MAX));
+}
+
+#[test]
+fn invalid_bits_are_rejected() {
+    let server = gmrs::test_server!();
+    let mut writer = net::start(server.state(), "test_raw", false).unwrap();
+    assert!(writer.write_uint(1, 0).is_err());
+    assert!(writer.write_int(1, 33).is_err());
+}
+
+#[test]
+fn receivers_are_removed() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    net::remove_receiver(state, "TEST_HIT");
+    assert!(server
+        .eval::<Option<OwnedRef>>("net.Receivers.test_hit")
+        .unwrap()
+        .is_none());
+    net::receive(state, "test_raw", |_, _, _| Ok(())).unwrap();
+    net::remove_all(state);
+    assert!(server.eval::<bool>("next(net.Receivers) == nil").unwrap());
+}
@@ -0,0 +1,57 @@
+use gmrs::{prelude::*, scripts::LuaScript};
+
+#[gmrs::function]
+fn greeting(name: String) -> lua::Result<String> {
+    Ok(format!("hello {}", name))
+}
+
+#[gmrs::entry(
+    module = "test_scripts",
+    scripts = [gmrs::include_lua!("lua/init.lua"), gmrs::include_lua!("lua/broken.lua")]
+)]
+fn open(state: LuaState) {
+    // the table is created by the entry function, the scripts add to it
+    gmrs::set_global_path(state, "test_scripts.greeting", NativeFunc::new(greeting)).unwrap();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn entry_scripts_run_after_the_entry_function() {
+    let server = gmrs::test_server!();
+    assert_eq!(
+        server
+            .eval::<String>("test_scripts.shout('world')")
+            .unwrap(),
+        "HELLO WORLD"
+    );
+}
+
+#[test]
+fn script_errors_are_reported_with_the_file_name() {
+    let server = gmrs::test_server!();
+    assert!(server
+        .eval::<bool>("test_scripts.loaded_before_error")
+        .unwrap());
+    server.assert_output_contains(
+        "failed to run 'lua/broken.lua': lua/broken.lua:4: broken on purpose",
+    );
+}
+
+#[test]
+fn scripts_return_their_results() {
+    let server = gmrs::test_server!();
+    let script = LuaScript::new("sum.lua", "local a, b = ... return a + b, 'done'");
+    let results = script.run(server.state(), (1.0, 2.0)).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.get::<f64>(server.state(), 0).unwrap(), 3.0);
+    assert_eq!(results.get::<String>(server.state(), 1).unwrap(), "done");
+    assert_eq!(results.get::<Option<f64>>(server.state(), 2).unwrap(), None);
+
+    let error = LuaScript::new("syntax.lua", "return +")
+        .run(server.state(), ())
+        .unwrap_err();
+    assert!(matches!(error, lua::Error::Syntax(_)), "{:?}", error);
+    assert!(error.to_string().starts_with("syntax.lua:1:"), "{}", error);
+}
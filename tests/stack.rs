@@ -0,0 +1,61 @@
+use gmrs::prelude::*;
+use std::collections::HashMap;
+
+#[gmrs::function]
+fn repeat_value(state: LuaState, count: usize) -> lua::Result<lua::MultiValue> {
+    Ok((0..count)
+        .map(|_| OwnedRef::new(state, 1))
+        .collect::<Vec<_>>()
+        .into())
+}
+
+#[gmrs::entry]
+fn open(state: LuaState) {
+    gmrs::set_global(state, "repeat_value", NativeFunc::new(repeat_value)).unwrap();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+#[test]
+fn stack_overflow_raises_an_error() {
+    let server = gmrs::test_server!();
+    assert_eq!(
+        server
+            .eval::<usize>("select('#', repeat_value(5))")
+            .unwrap(),
+        5
+    );
+    let (ok, message) = server
+        .eval::<(bool, String)>("pcall(repeat_value, 100000)")
+        .unwrap();
+    assert!(!ok);
+    assert_eq!(
+        message,
+        "stack overflow, cant grow the stack by 100000 values"
+    );
+    // the state is still usable
+    assert_eq!(
+        server
+            .eval::<usize>("select('#', repeat_value(1))")
+            .unwrap(),
+        1
+    );
+}
+
+#[test]
+fn collections_are_pushed_without_errors() {
+    let server = gmrs::test_server!();
+    let state = server.state();
+    let top = lua::top(state);
+    let list: lua::Sequence<f64> = (1..=100).map(f64::from).collect::<Vec<_>>().into();
+    assert_eq!(lua::push(state, list), 1);
+    let map: HashMap<String, f64> = HashMap::from([("a".to_string(), 1.0)]);
+    assert_eq!(lua::push(state, map), 1);
+    assert_eq!(lua::top(state), top + 2);
+    let map: HashMap<String, f64> = lua::get(state, -1).unwrap();
+    assert_eq!(map["a"], 1.0);
+    let list: lua::Sequence<f64> = lua::get(state, -2).unwrap();
+    assert_eq!(list.0.len(), 100);
+    lua::pop(state, 2);
+}
@@ -0,0 +1,130 @@
+use gmrs::{concommand, convar, hooks, net, prelude::*, HudPrint};
+use std::time::Duration;
+
+#[gmrs::entry]
+fn open(state: LuaState) {
+    hooks::add(state, "Ping", "shared", |state: LuaState| {
+        gmrs::print(state, "pong");
+    })
+    .unwrap()
+    .detach();
+    concommand::add(state, "test_shared", |_, _| Ok(()), "", gmrs::fcvar::NONE).unwrap();
+    net::add_network_string(state, "test_shared").unwrap();
+    net::receive(state, "test_shared", |_, _, _| Ok(())).unwrap();
+    convar::create(state, "test_shared", 1, gmrs::fcvar::NONE, "").unwrap();
+}
+
+#[gmrs::exit]
+fn close(_state: LuaState) {}
+
+fn has(server: &gmrs::testing::TestServer, expression: &str) -> bool {
+    server
+        .eval::<bool>(&format!("{} ~= nil", expression))
+        .unwrap()
+}
+
+fn registered(server: &gmrs::testing::TestServer) -> [bool; 4] {
+    [
+        has(server, "hook.GetTable().Ping.shared"),
+        has(server, "concommand.GetTable().test_shared"),
+        has(server, "net.Receivers.test_shared"),
+        has(server, "next(cvars.GetConVarCallbacks('test_shared'))"),
+    ]
+}
+
+#[test]
+fn closing_a_state_only_removes_its_own_registrations() {
+    let first = gmrs::test_server!();
+    let second = gmrs::test_server!();
+    assert_ne!(first.state_id(), second.state_id());
+    assert_eq!(registered(&first), [true; 4]);
+    assert_eq!(registered(&second), [true; 4]);
+
+    first.close();
+    assert_eq!(registered(&second), [true; 4]);
+    second.run("hook.Run('Ping')").unwrap();
+    second.assert_output_contains("pong");
+}
+
+#[test]
+fn functions_run_in_the_chosen_state() {
+    let first = gmrs::test_server!();
+    let second = gmrs::test_server!();
+    first.run("STATE_NAME = 'first'").unwrap();
+    second.run("STATE_NAME = 'second'").unwrap();
+
+    let id = second.state_id();
+    let worker = std::thread::spawn(move || {
+        let name: String =
+            gmrs::remote_execute_on(id, |state| gmrs::get_global(state, "STATE_NAME").unwrap());
+        let result: String = gmrs::remote_try_execute_on(id, |state| {
+            gmrs::call_global(state, "string.upper", "done")
+        });
+        (name, result)
+    });
+    assert!(second.think_until(Duration::from_secs(5), |_| worker.is_finished()));
+    assert_eq!(
+        worker.join().unwrap(),
+        ("second".to_string(), "DONE".to_string())
+    );
+}
+
+#[test]
+fn references_are_freed_by_the_state_that_owns_them() {
+    let first = gmrs::test_server!();
+    let second = gmrs::test_server!();
+    first
+        .run("weak = setmetatable({}, {__mode = 'v'}) weak[1] = {}")
+        .unwrap();
+    let reference: OwnedRef = first.eval("weak[1]").unwrap();
+    assert_eq!(reference.state(), first.state_id());
+
+    // dropped while the other state is the current one, the owner frees it on its next think
+    unsafe { gmrs::internal::set_lua_state_raw(second.state().ptr()) };
+    drop(reference);
+    first.run("collectgarbage()").unwrap();
+    assert!(has(&first, "weak[1]"));
+    first.think().unwrap();
+    first.run("collectgarbage()").unwrap();
+    assert!(!has(&first, "weak[1]"));
+}
+
+#[test]
+fn global_functions_are_cached_per_state() {
+    let first = gmrs::test_server!();
+    let second = gmrs::test_server!();
+    first
+        .run("function test_name() return 'first' end")
+        .unwrap();
+    second
+        .run("function test_name() return 'second' end")
+        .unwrap();
+    for _ in 0..2 {
+        assert_eq!(
+            gmrs::call_global::<_, String>(first.state(), "test_name", ()).unwrap(),
+            "first"
+        );
+        assert_eq!(
+            gmrs::call_global::<_, String>(second.state(), "test_name", ()).unwrap(),
+            "second"
+        );
+    }
+}
+
+#[test]
+fn print_to_uses_the_state_of_the_player() {
+    let first = gmrs::test_server!();
+    let second = gmrs::test_server!();
+    let ply: Player = second.eval("player.CreateNextBot('bot')").unwrap();
+    assert_eq!(ply.state(), second.state_id());
+    std::thread::spawn(move || gmrs::console::print_to(&ply, HudPrint::Talk, "hello"))
+        .join()
+        .unwrap();
+    first.think().unwrap();
+    assert!(second.think_until(Duration::from_secs(5), |server| {
+        server
+            .eval::<usize>("#player.GetAll()[1]:GetPrintedMessages()")
+            .unwrap()
+            == 1
+    }));
+}
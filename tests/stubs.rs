@@ -0,0 +1,187 @@
+use gmrs::{prelude::*, stubs::Stubs};
+
+/// Arithmetic helpers.
+#[gmrs::module(name = "test_arithmetic", version = "1.2.3")]
+mod arithmetic {
+    use gmrs::prelude::*;
+
+    /// Half a turn.
+    #[constant]
+    const PI: f64 = std::f64::consts::PI;
+
+    /// Adds two numbers.
+    #[function]
+    fn add(a: f64, b: f64) -> lua::Result<f64> {
+        Ok(a + b)
+    }
+
+    #[function(name = "sub")]
+    fn subtract(a: f64, b: Option<f64>) -> lua::Result<f64> {
+        Ok(a - b.unwrap_or(0.0))
+    }
+
+    /// Returns `a | b` for integers.
+    #[function]
+    fn either(a: u32, b: u32) -> lua::Result<()> {
+        let _ = a | b;
+        Ok(())
+    }
+
+    /// Returns the arguments unchanged.
+    #[function]
+    fn identity(values: lua::MultiValue) -> lua::Result<lua::MultiValue> {
+        Ok(values)
+    }
+
+    pub struct Counter(f64);
+    impl UserType for Counter {
+        fn build_metatable(builder: &mut MetatableBuilder<Self>) {
+            builder.method("get", counter_get);
+        }
+    }
+
+    #[gmrs::function(name = "get")]
+    fn counter_get(this: UserData<Counter>) -> lua::Result<f64> {
+        Ok(this.with(|counter| counter.0))
+    }
+
+    #[constructor]
+    fn new_counter(start: f64) -> lua::Result<UserData<Counter>> {
+        Ok(UserData::new(Counter(start)))
+    }
+}
+
+#[test]
+fn stubs_describe_the_module() {
+    let info = arithmetic::MODULE_INFO;
+    assert_eq!(info.name, "test_arithmetic");
+    assert_eq!(info.version, Some("1.2.3"));
+    let names: Vec<_> = info
+        .functions
+        .iter()
+        .map(|function| function.name)
+        .collect();
+    assert_eq!(names, ["add", "sub", "either", "identity", "Counter"]);
+
+    let stub = info.lua_stub();
+    assert!(
+        stub.starts_with(
+            "---@meta\n\n---Arithmetic helpers.\n---@class test_arithmetic\ntest_arithmetic = {}\n"
+        ),
+        "{}",
+        stub
+    );
+    assert!(
+        stub.contains("---@type string\ntest_arithmetic._VERSION = \"1.2.3\"\n"),
+        "{}",
+        stub
+    );
+    assert!(
+        stub.contains("---Half a turn.\n---@type number\ntest_arithmetic.PI = nil\n"),
+        "{}",
+        stub
+    );
+    assert!(stub.contains("---Adds two numbers.\n---@param a number\n---@param b number\n---@return number\nfunction test_arithmetic.add(a, b) end\n"), "{}", stub);
+    assert!(stub.contains("---@param a number\n---@param b number?\n---@return number\nfunction test_arithmetic.sub(a, b) end\n"), "{}", stub);
+    // `()` has no return types
+    assert!(
+        stub.contains("---@param b integer\nfunction test_arithmetic.either(a, b) end\n"),
+        "{}",
+        stub
+    );
+    assert!(
+        stub.contains(
+            "---@vararg any\n---@return any ...\nfunction test_arithmetic.identity(...) end\n"
+        ),
+        "{}",
+        stub
+    );
+    assert!(
+        stub.contains("---@return Counter\nfunction test_arithmetic.Counter(start) end\n"),
+        "{}",
+        stub
+    );
+
+    let markdown = info.markdown();
+    assert!(
+        markdown.starts_with("# test_arithmetic\nVersion `1.2.3`.\n\nArithmetic helpers.\n"),
+        "{}",
+        markdown
+    );
+    assert!(
+        markdown.contains("| `test_arithmetic.PI` | `number` | Half a turn. |"),
+        "{}",
+        markdown
+    );
+    assert!(
+        markdown.contains("### `test_arithmetic.sub(a: number, b: number?) -> number`"),
+        "{}",
+        markdown
+    );
+    assert!(
+        markdown.contains(
+            "### `test_arithmetic.either(a: integer, b: integer)`\nReturns `a | b` for integers."
+        ),
+        "{}",
+        markdown
+    );
+    assert!(
+        markdown.contains("### `test_arithmetic.identity(...) -> ...`"),
+        "{}",
+        markdown
+    );
+}
+
+/// Scales a vector.
+#[gmrs::function]
+fn scale(factor: f64, values: lua::MultiValue) -> lua::Result<(bool, lua::MultiValue)> {
+    Ok((factor != 0.0, values))
+}
+
+#[test]
+fn functions_outside_of_modules_are_globals() {
+    let stubs = Stubs::new("test_globals").function(gmrs::function_info!(scale));
+    assert_eq!(
+        stubs.lua_stub(),
+        "---@meta\n\n---Scales a vector.\n---@param factor number\n---@vararg any\n---@return boolean\n---@return any ...\nfunction scale(factor, ...) end\n"
+    );
+    assert_eq!(
+        stubs.markdown(),
+        "# test_globals\n\n## Functions\n\n### `scale(factor: number, ...) -> boolean, ...`\nScales a vector.\n"
+    );
+}
+
+#[test]
+fn stubs_are_written_to_a_directory() {
+    let directory = std::env::temp_dir().join(format!("gmrs_stubs_{}", std::process::id()));
+    arithmetic::MODULE_INFO.write(&directory).unwrap();
+    let stub = std::fs::read_to_string(directory.join("test_arithmetic.lua")).unwrap();
+    let markdown = std::fs::read_to_string(directory.join("test_arithmetic.md")).unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(stub, arithmetic::MODULE_INFO.lua_stub());
+    assert_eq!(markdown, arithmetic::MODULE_INFO.markdown());
+
+    let stubs = Stubs::new("test_globals")
+        .module(arithmetic::MODULE_INFO)
+        .function(gmrs::function_info!(scale));
+    stubs.write(&directory).unwrap();
+    let mut files: Vec<_> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        std::fs::read_to_string(directory.join("test_globals.lua")).unwrap(),
+        stubs.lua_stub()
+    );
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(
+        files,
+        [
+            "test_arithmetic.lua",
+            "test_arithmetic.md",
+            "test_globals.lua",
+            "test_globals.md"
+        ]
+    );
+}